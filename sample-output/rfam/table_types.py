@@ -5,7 +5,7 @@
 # If this is in error, please check the --minimum-python-version (-p) argument
 
 import datetime
-from typing import Any, TypedDict
+from typing import TypedDict
 
 
 class AlignmentAndTree(TypedDict):
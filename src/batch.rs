@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+/// One database of a `--batch` run, which is generated as if its fields were passed on the command line
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BatchEntry {
+    /// Identifies the entry in the summary, instead of its output filename
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) connection_string: Option<String>,
+    #[serde(default)]
+    pub(crate) from_sql_file: Option<PathBuf>,
+    pub(crate) schema: Vec<String>,
+    pub(crate) output_filename: PathBuf,
+    /// Extra command line arguments for this entry only, e.g. `["--output-style", "pydantic"]`
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+}
+
+impl BatchEntry {
+    /// The name of the entry in the summary
+    pub(crate) fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.output_filename.to_string_lossy().to_string())
+    }
+
+    /// Builds the command line of this entry, which is the arguments shared by every entry followed by its own
+    pub(crate) fn as_command_line(&self, shared_args: &[String]) -> Vec<String> {
+        let mut command_line = shared_args.to_vec();
+        if let Some(connection_string) = &self.connection_string {
+            command_line.extend([
+                String::from("--connection-string"),
+                connection_string.clone(),
+            ]);
+        }
+        if let Some(from_sql_file) = &self.from_sql_file {
+            command_line.extend([
+                String::from("--from-sql-file"),
+                from_sql_file.to_string_lossy().to_string(),
+            ]);
+        }
+        for schema in &self.schema {
+            command_line.extend([String::from("--schema"), schema.clone()]);
+        }
+        command_line.extend([
+            String::from("--output-filename"),
+            self.output_filename.to_string_lossy().to_string(),
+        ]);
+        command_line.extend(self.args.iter().cloned());
+        command_line
+    }
+}
+
+/// Reads the JSON list of `BatchEntry`s of a `--batch` run
+pub(crate) fn read_batch_file(path: &Path) -> Result<Vec<BatchEntry>, anyhow::Error> {
+    let json = fs::read_to_string(path)
+        .context(format!("Unable to read {} file.", path.to_string_lossy()))?;
+
+    serde_json::from_str(&json).context(format!(
+        "Unable to parse {} as a list of batch entries",
+        path.to_string_lossy()
+    ))
+}
+
+/// Removes the `--batch` arguments from a command line, so that the rest can be shared by every entry
+pub(crate) fn shared_batch_args(command_line: &[String]) -> Vec<String> {
+    let mut shared_args = vec![];
+    let mut args = command_line.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch" | "--batch-output" => {
+                args.next(); // the batch file
+            }
+            "--batch-keep-going" => {}
+            _ if arg.starts_with("--batch=") || arg.starts_with("--batch-output=") => {}
+            _ => shared_args.push(arg.clone()),
+        }
+    }
+    shared_args
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_the_command_line_of_an_entry() {
+        let entry: BatchEntry = serde_json::from_str(
+            r#"{
+                "connection_string": "postgres://localhost/users",
+                "schema": ["public", "auth"],
+                "output_filename": "users_types.py",
+                "args": ["--output-style", "pydantic"]
+            }"#,
+        )
+        .unwrap();
+        let shared_args = shared_batch_args(&[
+            String::from("db-introspector-gadget"),
+            String::from("--batch"),
+            String::from("batch.json"),
+            String::from("--canonical"),
+            String::from("--batch-keep-going"),
+        ]);
+
+        assert_eq!(
+            entry.as_command_line(&shared_args),
+            vec![
+                "db-introspector-gadget",
+                "--canonical",
+                "--connection-string",
+                "postgres://localhost/users",
+                "--schema",
+                "public",
+                "--schema",
+                "auth",
+                "--output-filename",
+                "users_types.py",
+                "--output-style",
+                "pydantic",
+            ]
+        );
+        assert_eq!(entry.label(), "users_types.py");
+    }
+}
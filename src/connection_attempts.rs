@@ -0,0 +1,190 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    future::Future,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Options for retrying a database connection that fails, e.g. while a CI database is still starting up
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectionRetryOptions {
+    /// How many times a failed connection is retried before giving up
+    pub(crate) retries: u32,
+    /// How long to wait between attempts
+    pub(crate) delay: Duration,
+    /// A file that every attempt is appended to as a JSON line, for post-mortem analysis
+    pub(crate) log_path: Option<PathBuf>,
+}
+
+/// The outcome of a single attempt to connect to the database
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConnectionAttempt {
+    /// Starts at 1
+    pub(crate) attempt: u32,
+    pub(crate) elapsed: Duration,
+    /// The connection error, or `None` if the attempt succeeded
+    pub(crate) error: Option<String>,
+}
+
+impl ConnectionAttempt {
+    /// Builds the JSON line recorded in the `--connection-log` file for this attempt
+    pub(crate) fn as_log_line(&self, schema: &str) -> String {
+        serde_json::json!({
+            "schema": schema,
+            "attempt": self.attempt,
+            "elapsed_ms": self.elapsed.as_millis() as u64,
+            "outcome": if self.error.is_some() { "error" } else { "connected" },
+            "error": self.error,
+        })
+        .to_string()
+    }
+}
+
+/// Calls `connect` until it succeeds or `options.retries` retries have failed, waiting `options.delay`
+/// between attempts, and reports every attempt to `on_attempt` as it finishes.
+///
+/// The last connection error is returned if every attempt fails.
+pub(crate) async fn connect_with_retries<C, E, F, Fut>(
+    options: &ConnectionRetryOptions,
+    mut connect: F,
+    mut on_attempt: impl FnMut(&ConnectionAttempt),
+) -> Result<C, anyhow::Error>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<C, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        let started_at = Instant::now();
+        let result = connect().await;
+
+        on_attempt(&ConnectionAttempt {
+            attempt,
+            elapsed: started_at.elapsed(),
+            error: result.as_ref().err().map(|error| error.to_string()),
+        });
+
+        match result {
+            Ok(connection) => return Ok(connection),
+            Err(error) if attempt > options.retries => {
+                anyhow::bail!("Unable to connect after {attempt} attempt(s): {error}")
+            }
+            Err(_) => {
+                tokio::time::sleep(options.delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Reports a connection attempt on stderr if it failed, and appends it to the `--connection-log` file if one is set
+pub(crate) fn record_connection_attempt(
+    options: &ConnectionRetryOptions,
+    schema: &str,
+    attempt: &ConnectionAttempt,
+) {
+    if let Some(error) = &attempt.error {
+        eprintln!(
+            "Connection attempt {} for schema `{schema}` failed after {} ms: {error}",
+            attempt.attempt,
+            attempt.elapsed.as_millis()
+        );
+    }
+
+    if let Some(log_path) = &options.log_path {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)
+            .and_then(|mut file| writeln!(file, "{}", attempt.as_log_line(schema)));
+
+        if let Err(error) = written {
+            eprintln!(
+                "Warning: unable to write to the connection log {}: {error}",
+                log_path.to_string_lossy()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn retries_until_connected_and_records_each_attempt() {
+        let options = ConnectionRetryOptions {
+            retries: 3,
+            ..Default::default()
+        };
+
+        let mut remaining_failures = 2;
+        let mut attempts = vec![];
+        let result = connect_with_retries(
+            &options,
+            || {
+                let result = if remaining_failures > 0 {
+                    Err("connection refused")
+                } else {
+                    Ok("connection")
+                };
+                remaining_failures -= 1;
+                async move { result }
+            },
+            |attempt| attempts.push(attempt.clone()),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "connection");
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|attempt| (attempt.attempt, attempt.error.as_deref()))
+                .collect::<Vec<(u32, Option<&str>)>>(),
+            vec![
+                (1, Some("connection refused")),
+                (2, Some("connection refused")),
+                (3, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_retry() {
+        let options = ConnectionRetryOptions {
+            retries: 1,
+            ..Default::default()
+        };
+
+        let mut attempts = vec![];
+        let result = connect_with_retries(
+            &options,
+            || async { Err::<(), _>("connection refused") },
+            |attempt| attempts.push(attempt.clone()),
+        )
+        .await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unable to connect after 2 attempt(s): connection refused"
+        );
+        assert_eq!(attempts.len(), 2);
+    }
+
+    #[test]
+    fn writes_attempts_as_json_lines() {
+        let attempt = ConnectionAttempt {
+            attempt: 2,
+            elapsed: Duration::from_millis(1500),
+            error: Some(String::from("timed out")),
+        };
+
+        assert_eq!(
+            attempt.as_log_line("public"),
+            r#"{"attempt":2,"elapsed_ms":1500,"error":"timed out","outcome":"error","schema":"public"}"#
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Caches the results of the enrichment queries that run after the main introspection query,
+/// like the distinct value sampling of `--infer-enums`, in a temporary directory scoped to a single run.
+///
+/// When an introspection attempt fails partway through and is retried, the columns that were already
+/// enriched are read back from the cache instead of being queried again.
+/// The cache is only an optimization, so failing to read or write it is never an error
+#[derive(Debug, Clone)]
+pub(crate) struct EnrichmentCache {
+    directory: PathBuf,
+}
+
+impl EnrichmentCache {
+    /// Creates an empty cache in a new temporary directory for this run
+    pub(crate) fn for_run() -> Result<Self, anyhow::Error> {
+        let started_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let directory = std::env::temp_dir().join(format!(
+            "db-introspector-gadget-{}-{started_at}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory)?;
+        Ok(EnrichmentCache { directory })
+    }
+
+    /// The file a column's cached values are stored in, named after a hash of the column's location
+    /// since identifiers can contain characters that aren't valid in file names
+    fn path(&self, schema: &str, table_name: &str, column_name: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        (schema, table_name, column_name).hash(&mut hasher);
+        self.directory
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    /// Reads the cached values of a column, if an earlier attempt of this run stored them
+    pub(crate) fn get(
+        &self,
+        schema: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Option<Vec<String>> {
+        let contents = fs::read_to_string(self.path(schema, table_name, column_name)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Stores the values of a column for later attempts of this run
+    pub(crate) fn put(&self, schema: &str, table_name: &str, column_name: &str, values: &[String]) {
+        let written = serde_json::to_string(values)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(self.path(schema, table_name, column_name), json)?));
+
+        if let Err(error) = written {
+            eprintln!(
+                "Warning: unable to cache the enrichment of `{table_name}.{column_name}`: {error}"
+            );
+        }
+    }
+
+    /// Deletes the cache directory at the end of the run
+    pub(crate) fn remove(&self) {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reuses_cached_values_within_a_run() {
+        let cache = EnrichmentCache::for_run().unwrap();
+        let values = vec![String::from("\"active\""), String::from("\"disabled\"")];
+
+        assert_eq!(cache.get("public", "users", "status"), None);
+        cache.put("public", "users", "status", &values);
+
+        // a retried attempt gets its own clone of the options, and with it the cache
+        let retried_attempt_cache = cache.clone();
+        assert_eq!(
+            retried_attempt_cache.get("public", "users", "status"),
+            Some(values)
+        );
+        assert_eq!(retried_attempt_cache.get("auth", "users", "status"), None);
+
+        cache.remove();
+        assert_eq!(cache.get("public", "users", "status"), None);
+    }
+}
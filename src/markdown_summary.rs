@@ -0,0 +1,97 @@
+use crate::{python_types::PythonTypedDict, MinimumPythonVersion};
+
+/// Escapes a value for a Markdown table cell, where a `|` would end the cell and a line break the row
+fn as_markdown_cell(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .lines()
+        .collect::<Vec<&str>>()
+        .join("<br>")
+}
+
+/// Writes a Markdown data dictionary of the tables, with a section per table listing each column's
+/// Python type, nullability, and comment
+pub(crate) fn write_markdown_summary(
+    dicts: &[PythonTypedDict],
+    minimum_python_version: MinimumPythonVersion,
+) -> String {
+    let mut result = String::from("# Data dictionary\n");
+
+    for dict in dicts {
+        result.push_str(&format!("\n## {}\n\n", as_markdown_cell(&dict.raw_name)));
+        result.push_str(&format!("Python type: `{}`\n\n", dict.name));
+        result.push_str("| Column | Python type | Nullable | Comment |\n");
+        result.push_str("| --- | --- | --- | --- |\n");
+        for property in &dict.properties {
+            result.push_str(&format!(
+                "| {} | `{}` | {} | {} |\n",
+                as_markdown_cell(&property.name),
+                as_markdown_cell(&property.as_property_type_str(minimum_python_version)),
+                if property.nullable { "yes" } else { "no" },
+                as_markdown_cell(property.comment.as_deref().unwrap_or_default())
+            ));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::python_types::{PythonDataType, PythonDictProperty};
+    use indoc::indoc;
+
+    #[test]
+    fn writes_a_section_per_table_with_a_row_per_column() {
+        let property = |name: &str, nullable: bool, comment: Option<&str>| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type: PythonDataType::String,
+            comment: comment.map(String::from),
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Orders"),
+                raw_name: String::from("orders"),
+                properties: vec![property("status", false, Some("pending | paid"))],
+            },
+            PythonTypedDict {
+                name: String::from("Users"),
+                raw_name: String::from("users"),
+                properties: vec![
+                    property("email", true, Some("Where receipts\nare sent")),
+                    property("name", false, None),
+                ],
+            },
+        ];
+
+        let result = write_markdown_summary(&dicts, MinimumPythonVersion::Python3_10);
+
+        let expected = indoc! {r#"
+            # Data dictionary
+
+            ## orders
+
+            Python type: `Orders`
+
+            | Column | Python type | Nullable | Comment |
+            | --- | --- | --- | --- |
+            | status | `str` | no | pending \| paid |
+
+            ## users
+
+            Python type: `Users`
+
+            | Column | Python type | Nullable | Comment |
+            | --- | --- | --- | --- |
+            | email | `str \| None` | yes | Where receipts<br>are sent |
+            | name | `str` | no |  |
+        "#};
+
+        assert_eq!(result, expected);
+    }
+}
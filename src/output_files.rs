@@ -0,0 +1,157 @@
+use std::{fs, io::Write, num::NonZeroUsize, path::Path, thread};
+
+use anyhow::Context;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// Represents a single generated file, with a `path` relative to the output directory or archive root
+#[derive(Debug, PartialEq)]
+pub(crate) struct GeneratedFile {
+    pub(crate) path: String,
+    pub(crate) contents: String,
+}
+
+/// Writes every `GeneratedFile` into the given directory, creating it if it doesn't exist
+///
+/// The files are split between up to `concurrency` threads, since each write is independent
+pub(crate) fn write_files_to_directory(
+    directory: &Path,
+    files: &[GeneratedFile],
+    concurrency: NonZeroUsize,
+) -> Result<(), anyhow::Error> {
+    fs::create_dir_all(directory).context(format!(
+        "Unable to create {} directory.",
+        directory.to_string_lossy()
+    ))?;
+
+    let write_files = |files: &[GeneratedFile]| -> Result<(), anyhow::Error> {
+        for file in files {
+            let file_path = directory.join(&file.path);
+            fs::write(&file_path, &file.contents).context(format!(
+                "Unable to create {} file.",
+                file_path.to_string_lossy()
+            ))?;
+        }
+        Ok(())
+    };
+
+    if concurrency.get() == 1 || files.len() <= 1 {
+        return write_files(files);
+    }
+
+    let chunk_size = files.len().div_ceil(concurrency.get());
+    thread::scope(|scope| {
+        files
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || write_files(chunk)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .try_for_each(|handle| {
+                handle
+                    .join()
+                    .map_err(|_| anyhow::anyhow!("A file writing thread panicked"))?
+            })
+    })
+}
+
+/// Writes every `GeneratedFile` as an entry of a new ZIP archive at the given path
+pub(crate) fn write_files_to_zip(
+    zip_path: &Path,
+    files: &[GeneratedFile],
+) -> Result<(), anyhow::Error> {
+    let zip_file = fs::File::create(zip_path).context(format!(
+        "Unable to create {} file.",
+        zip_path.to_string_lossy()
+    ))?;
+
+    let mut zip = ZipWriter::new(zip_file);
+    for file in files {
+        zip.start_file(file.path.as_str(), SimpleFileOptions::default())?;
+        zip.write_all(file.contents.as_bytes())?;
+    }
+    zip.finish()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    fn generated_files() -> Vec<GeneratedFile> {
+        vec![
+            GeneratedFile {
+                path: String::from("users.py"),
+                contents: String::from("class Users(TypedDict):\n    id: int\n"),
+            },
+            GeneratedFile {
+                path: String::from("__init__.py"),
+                contents: String::from("from .users import Users\n"),
+            },
+            GeneratedFile {
+                path: String::from("py.typed"),
+                contents: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn writes_files_to_zip_archive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let zip_path = temp_dir.path().join("types.zip");
+
+        write_files_to_zip(&zip_path, &generated_files()).unwrap();
+
+        let mut archive = ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut entries = archive.file_names().collect::<Vec<&str>>();
+        entries.sort();
+        assert_eq!(entries, vec!["__init__.py", "py.typed", "users.py"]);
+
+        let mut init_contents = String::new();
+        archive
+            .by_name("__init__.py")
+            .unwrap()
+            .read_to_string(&mut init_contents)
+            .unwrap();
+        assert_eq!(init_contents, "from .users import Users\n");
+    }
+
+    #[test]
+    fn writes_files_to_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let directory = temp_dir.path().join("table_types");
+
+        write_files_to_directory(&directory, &generated_files(), NonZeroUsize::MIN).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(directory.join("users.py")).unwrap(),
+            "class Users(TypedDict):\n    id: int\n"
+        );
+        assert!(directory.join("py.typed").exists());
+    }
+
+    #[test]
+    fn writes_files_to_directory_concurrently() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let directory = temp_dir.path().join("table_types");
+        let files = (0..100)
+            .map(|i| GeneratedFile {
+                path: format!("table_{i}.py"),
+                contents: format!("class Table{i}(TypedDict):\n    id: int\n"),
+            })
+            .collect::<Vec<GeneratedFile>>();
+
+        write_files_to_directory(&directory, &files, NonZeroUsize::new(8).unwrap()).unwrap();
+
+        for file in &files {
+            assert_eq!(
+                fs::read_to_string(directory.join(&file.path)).unwrap(),
+                file.contents
+            );
+        }
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), files.len());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::Context;
+
+/// Pipes the generated file contents through an external command (e.g. `ruff format -`) and returns its stdout,
+/// so that users can format the output with their own toolchain.
+///
+/// The command is split on whitespace, and a non-zero exit status is returned as an error
+/// so that nothing gets written.
+pub(crate) fn post_process(contents: &str, command: &str) -> Result<String, anyhow::Error> {
+    let mut command_parts = command.split_whitespace();
+    let program = command_parts
+        .next()
+        .context("The post-process command must not be empty")?;
+
+    let mut child = Command::new(program)
+        .args(command_parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!("Unable to run post-process command `{command}`"))?;
+
+    // writes stdin on its own thread so a command that streams its output can't deadlock us
+    let mut stdin = child.stdin.take().context("Unable to open stdin")?;
+    let input = contents.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("Unable to write to post-process command `{command}`"))??;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Post-process command `{command}` failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout).context(format!(
+        "Post-process command `{command}` didn't output valid UTF-8"
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pipes_contents_through_command() {
+        let contents = "class Users(TypedDict):\n    id: int\n";
+
+        assert_eq!(post_process(contents, "cat").unwrap(), contents);
+    }
+
+    #[test]
+    fn passes_arguments_to_command() {
+        assert_eq!(post_process("a\nb\n", "head -n 1").unwrap(), "a\n");
+    }
+
+    #[test]
+    fn errors_on_non_zero_exit() {
+        assert!(post_process("anything", "false").is_err());
+    }
+
+    #[test]
+    fn errors_on_missing_command() {
+        assert!(post_process("anything", "db-introspector-gadget-not-a-command").is_err());
+        assert!(post_process("anything", "  ").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use itertools::Itertools;
+
+use crate::python_types::{PythonDataType, PythonDictProperty, PythonTypedDict};
+
+/// Words of the proto3 language and its scalar types, which get a trailing `_` when used as field names
+/// so that the generated file doesn't trip up `protoc` or the code generators of other languages
+const PROTO_KEYWORDS: &[&str] = &[
+    "bool", "bytes", "double", "enum", "extend", "fixed32", "fixed64", "float", "import", "int32",
+    "int64", "map", "max", "message", "oneof", "option", "optional", "package", "public",
+    "repeated", "reserved", "returns", "rpc", "service", "sfixed32", "sfixed64", "sint32",
+    "sint64", "stream", "string", "syntax", "to", "uint32", "uint64", "weak",
+];
+
+impl PythonDataType {
+    /// Convert a `PythonDataType` into its Protocol Buffers field type representation
+    pub(crate) fn as_proto_type_str(&self) -> String {
+        match self {
+            PythonDataType::String => "string",
+            PythonDataType::Integer => "int64",
+            PythonDataType::Float => "double",
+            PythonDataType::Decimal => "string", // proto has no decimal scalar, and a double would lose precision
+            PythonDataType::Boolean => "bool",
+            PythonDataType::DateTime | PythonDataType::AwareDateTime => "google.protobuf.Timestamp",
+            PythonDataType::Date => "string", // ISO 8601, since there's no well-known type for dates
+            PythonDataType::Time => "string", // ISO 8601, like dates
+            PythonDataType::Interval => "google.protobuf.Duration",
+            PythonDataType::Binary => "bytes",
+            PythonDataType::Json => "string",
+            PythonDataType::Uuid => "string",
+            PythonDataType::List(element) => return element.as_proto_type_str(),
+            PythonDataType::Literal(_) => "string",
+            PythonDataType::NewType { base, .. } => return base.as_proto_type_str(),
+            PythonDataType::Nested(name) => return name.clone(),
+            PythonDataType::Any => "string", // unknown database types are read as their text representation
+        }
+        .to_string()
+    }
+}
+
+impl PythonDictProperty {
+    /// Builds the proto field name for this property, replacing the characters identifiers can't hold
+    /// with `_` and escaping proto keywords
+    pub(crate) fn as_proto_field_name(&self) -> String {
+        let name = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("field_{name}")
+        } else if PROTO_KEYWORDS.contains(&name.as_str()) {
+            format!("{name}_")
+        } else {
+            name
+        }
+    }
+
+    /// Builds the label and type of this property, e.g. `optional string`, or `repeated int64` for arrays,
+    /// which are never optional
+    pub(crate) fn as_proto_field_type_str(&self) -> String {
+        match &self.data_type {
+            PythonDataType::List(_) => format!("repeated {}", self.data_type.as_proto_type_str()),
+            data_type if self.nullable => format!("optional {}", data_type.as_proto_type_str()),
+            data_type => data_type.as_proto_type_str(),
+        }
+    }
+}
+
+impl PythonTypedDict {
+    /// Outputs a Protocol Buffers source string representation of this table as a `message`,
+    /// numbering its fields in column order
+    pub(crate) fn as_proto_message_str(&self) -> String {
+        let mut result = format!("message {} {{\n", self.name);
+
+        for (field_number, property) in (1..).zip(&self.properties) {
+            result.push_str(
+                format!(
+                    "  {} {} = {};\n",
+                    property.as_proto_field_type_str(),
+                    property.as_proto_field_name(),
+                    field_number
+                )
+                .as_str(),
+            );
+        }
+
+        result.push_str("}\n");
+
+        result
+    }
+}
+
+/// Writes the `Vec<PythonTypedDict>` into a proto3 source string of `message` definitions
+/// that can then later be written to a file inside `main()`
+pub(crate) fn write_proto_messages_to_str(dicts: Vec<PythonTypedDict>) -> String {
+    let mut result = String::from(
+        "// This file was generated by db-introspector-gadget\n// https://github.com/sesgoe/db-introspector-gadget\n\nsyntax = \"proto3\";\n\n",
+    );
+
+    let uses_timestamp = dicts
+        .iter()
+        .flat_map(|dict| &dict.properties)
+        .any(|p| p.data_type.uses(&PythonDataType::DateTime));
+
+    let uses_duration = dicts
+        .iter()
+        .flat_map(|dict| &dict.properties)
+        .any(|p| p.data_type.uses(&PythonDataType::Interval));
+
+    if uses_duration {
+        result.push_str("import \"google/protobuf/duration.proto\";\n");
+    }
+    if uses_timestamp {
+        result.push_str("import \"google/protobuf/timestamp.proto\";\n");
+    }
+    if uses_duration || uses_timestamp {
+        result.push('\n');
+    }
+
+    let proto_messages_str = dicts
+        .iter()
+        .filter(|dict| !dict.name.contains('$')) // prevents weirdness with some system tables
+        .filter(|dict| !dict.name.chars().next().unwrap().is_numeric())
+        .sorted_by_key(|f| f.name.clone())
+        .map(|dict| dict.as_proto_message_str())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    result.push_str(proto_messages_str.as_str());
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_proto_message_str() {
+        let property = |name: &str, nullable, data_type| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dict = PythonTypedDict {
+            name: String::from("User"),
+            raw_name: String::from("user"),
+            properties: vec![
+                property("id", false, PythonDataType::Integer),
+                property("name", true, PythonDataType::String),
+                property("message", false, PythonDataType::String),
+                property(
+                    "tags",
+                    true,
+                    PythonDataType::List(Box::new(PythonDataType::String)),
+                ),
+                property("2fa enabled", false, PythonDataType::Boolean),
+            ],
+        };
+
+        assert_eq!(
+            dict.as_proto_message_str(),
+            indoc! {"
+                message User {
+                  int64 id = 1;
+                  optional string name = 2;
+                  string message_ = 3;
+                  repeated string tags = 4;
+                  bool field_2fa_enabled = 5;
+                }
+            "}
+        );
+    }
+
+    #[test]
+    fn writes_proto_messages_to_string() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("created_at"),
+                    nullable: false,
+                    data_type: PythonDataType::DateTime,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("price"),
+                    nullable: true,
+                    data_type: PythonDataType::Float,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        assert_eq!(
+            write_proto_messages_to_str(dicts),
+            indoc! {r#"
+                // This file was generated by db-introspector-gadget
+                // https://github.com/sesgoe/db-introspector-gadget
+
+                syntax = "proto3";
+
+                import "google/protobuf/timestamp.proto";
+
+                message ATable {
+                  optional double price = 1;
+                }
+
+                message BTable {
+                  google.protobuf.Timestamp created_at = 1;
+                }
+            "#}
+        );
+    }
+}
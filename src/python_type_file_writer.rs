@@ -1,476 +1,5311 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    mem::discriminant,
+    num::NonZeroUsize,
+};
 
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use glob::Pattern;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::{
-    db_introspector::TableColumnDefinition,
-    python_types::{PythonDictProperty, PythonTypedDict},
-    MinimumPythonVersion,
+    db_introspector::{TableColumnDefinition, MAX_INFERRED_ENUM_VALUES},
+    output_files::GeneratedFile,
+    python_types::{
+        as_polars_schema_name, as_python_string_literal, DataclassOptions, DialectVersion,
+        PythonDataType, PythonDictProperty, PythonTypedDict, Totality, AWARE_DATETIME_ALIAS,
+    },
+    MinimumPythonVersion, OutputStyle, PydanticJson, TypedDictNameSource, TypingImportStyle,
 };
 
+/// Options that control how database columns are converted into `PythonTypedDict`s
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConversionOptions {
+    /// Only keeps columns of these types, unless empty
+    pub(crate) include_column_types: Vec<PythonDataType>,
+    /// Drops columns of these types
+    pub(crate) exclude_column_types: Vec<PythonDataType>,
+    /// Forces columns whose names match any of these patterns to be non-nullable,
+    /// regardless of what the database reports
+    pub(crate) assume_not_null: Vec<Pattern>,
+    /// Types MySQL `tinyint(1)` columns, which are otherwise read as booleans, as `Literal[0, 1]`,
+    /// and `tinyint`s of other widths as `int`
+    pub(crate) bool_as_int_literal: bool,
+    /// Truncates column comments longer than this many characters, ending them with an ellipsis
+    pub(crate) comment_max_length: Option<NonZeroUsize>,
+    /// The server version, which types the server doesn't have yet are typed as `Any` for
+    pub(crate) dialect_version: Option<DialectVersion>,
+    /// Reads the `data_type`s as SQL Server type names, some of which mean something else on MySQL
+    pub(crate) sql_server: bool,
+    /// Types exact numeric columns (MySQL `decimal`, Postgres `numeric`) as `decimal.Decimal` instead of `float`
+    pub(crate) decimal_as_decimal: bool,
+    /// Normalizes table and column names to NFKC, like Python does for identifiers, so that names which only
+    /// differ in their Unicode encoding are detected as the same name
+    pub(crate) normalize_identifiers: bool,
+    /// Inserted verbatim before each class name, e.g. `Db` for `DbUsers`
+    pub(crate) class_name_prefix: String,
+    /// Inserted verbatim after each class name, e.g. `Row` for `UsersRow`
+    pub(crate) class_name_suffix: String,
+}
+
+impl ConversionOptions {
+    /// The Python identifier a table or column name is written as
+    fn identifier(&self, name: &str) -> String {
+        if self.normalize_identifiers {
+            name.nfkc().collect()
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Converts a column's database `data_type` into the `PythonDataType` the filters and the output see
+    fn column_data_type(&self, data_type: &str) -> PythonDataType {
+        let python_data_type = if self.sql_server {
+            PythonDataType::from_sql_server(data_type.to_string())
+        } else {
+            PythonDataType::from_versioned(data_type.to_string(), self.dialect_version)
+        };
+        if self.decimal_as_decimal && PythonDataType::is_exact_numeric(data_type) {
+            python_data_type.with_decimals()
+        } else {
+            python_data_type
+        }
+    }
+
+    /// Whether a column of the given type survives the `--include-column-types` filter
+    fn includes_column_type(&self, data_type: &PythonDataType) -> bool {
+        self.include_column_types.is_empty()
+            || self
+                .include_column_types
+                .iter()
+                .any(|other| discriminant(other) == discriminant(data_type))
+    }
+
+    /// Whether a column of the given type is dropped by the `--exclude-column-types` filter
+    fn excludes_column_type(&self, data_type: &PythonDataType) -> bool {
+        self.exclude_column_types
+            .iter()
+            .any(|other| discriminant(other) == discriminant(data_type))
+    }
+
+    /// Whether a column of the given type survives the `--include-column-types`/`--exclude-column-types` filters
+    fn keeps_column_type(&self, data_type: &PythonDataType) -> bool {
+        self.includes_column_type(data_type) && !self.excludes_column_type(data_type)
+    }
+
+    /// Whether a column survives the filters with a type that has no Python mapping, and is typed as `Any`
+    fn is_unmapped_column(&self, column: &TableColumnDefinition) -> bool {
+        let data_type = self.column_data_type(&column.data_type);
+        self.keeps_column_type(&data_type) && data_type.uses(&PythonDataType::Any)
+    }
+}
+
+/// Builds a warning for every table that the column filters leave without any columns, naming the filter
+/// that dropped the last of them, since those tables are skipped instead of being written as empty classes
+pub(crate) fn emptied_table_warnings(
+    table_column_definitions: &[TableColumnDefinition],
+    options: &ConversionOptions,
+) -> Vec<String> {
+    table_column_definitions
+        .iter()
+        .into_group_map_by(|column| (column.table_name.as_str(), column.schema.as_deref()))
+        .into_iter()
+        .sorted_by_key(|(table, _)| *table)
+        .filter_map(|((table_name, _), columns)| {
+            let data_types = columns
+                .iter()
+                .map(|column| options.column_data_type(&column.data_type))
+                .collect::<Vec<PythonDataType>>();
+            if data_types
+                .iter()
+                .any(|data_type| options.keeps_column_type(data_type))
+            {
+                return None;
+            }
+
+            let filter = if data_types
+                .iter()
+                .any(|data_type| options.includes_column_type(data_type))
+            {
+                "--exclude-column-types"
+            } else {
+                "--include-column-types"
+            };
+            Some(format!(
+                "skipping table `{table_name}` because {filter} leaves it without any columns"
+            ))
+        })
+        .collect()
+}
+
+/// Builds a warning naming the distinct database types of the kept columns that have no Python mapping
+/// and were typed as `Any`, e.g. `2 column types mapped to Any: hstore, ltree`
+pub(crate) fn unmapped_type_warning(
+    table_column_definitions: &[TableColumnDefinition],
+    options: &ConversionOptions,
+) -> Option<String> {
+    let unmapped_types = table_column_definitions
+        .iter()
+        .filter(|column| options.is_unmapped_column(column))
+        .map(|column| column.data_type.trim_end_matches("[]"))
+        .unique()
+        .sorted()
+        .collect::<Vec<&str>>();
+
+    match unmapped_types.as_slice() {
+        [] => None,
+        [data_type] => Some(format!("1 column type mapped to Any: {data_type}")),
+        _ => Some(format!(
+            "{} column types mapped to Any: {}",
+            unmapped_types.len(),
+            unmapped_types.join(", ")
+        )),
+    }
+}
+
+/// Builds the error of `--fail-on-any`, naming every kept column typed as `Any` with its database type,
+/// or `None` if every column has a Python mapping
+pub(crate) fn unmapped_column_error(
+    table_column_definitions: &[TableColumnDefinition],
+    options: &ConversionOptions,
+) -> Option<String> {
+    let unmapped_columns = table_column_definitions
+        .iter()
+        .filter(|column| options.is_unmapped_column(column))
+        .map(|column| {
+            format!(
+                "`{}.{}` ({})",
+                column.table_name, column.column_name, column.data_type
+            )
+        })
+        .collect::<Vec<String>>();
+
+    (!unmapped_columns.is_empty()).then(|| {
+        format!(
+            "Columns typed as Any because their types have no Python mapping: {}",
+            unmapped_columns.join(", ")
+        )
+    })
+}
+
+/// Shortens a comment to at most `max_length` characters, including the ellipsis that marks it as truncated
+fn truncate_comment(comment: String, max_length: NonZeroUsize) -> String {
+    if comment.chars().count() <= max_length.get() {
+        return comment;
+    }
+
+    let kept = comment
+        .chars()
+        .take(max_length.get() - 1)
+        .collect::<String>();
+    format!("{}…", kept.trim_end())
+}
+
+/// Names the class of each table, prefixing tables of the same name in several schemas with their schema
+struct ClassNamer<'a> {
+    tables_in_several_schemas: HashSet<String>,
+    options: &'a ConversionOptions,
+}
+
+impl<'a> ClassNamer<'a> {
+    fn new(
+        table_column_definitions: &[TableColumnDefinition],
+        options: &'a ConversionOptions,
+    ) -> Self {
+        let tables_in_several_schemas = table_column_definitions
+            .iter()
+            .filter_map(|column| Some((column.table_name.clone(), column.schema.clone()?)))
+            .unique()
+            .counts_by(|(table_name, _)| table_name)
+            .into_iter()
+            .filter(|(_, schema_count)| *schema_count > 1)
+            .map(|(table_name, _)| table_name)
+            .collect();
+
+        ClassNamer {
+            tables_in_several_schemas,
+            options,
+        }
+    }
+
+    fn class_name(&self, column: &TableColumnDefinition) -> String {
+        let class_name = match &column.schema {
+            Some(schema) if self.tables_in_several_schemas.contains(&column.table_name) => self
+                .options
+                .identifier(&format!("{schema}_{}", column.table_name))
+                .to_case(Case::Pascal),
+            _ => self
+                .options
+                .identifier(&column.table_name)
+                .to_case(Case::Pascal),
+        };
+        format!(
+            "{}{class_name}{}",
+            self.options.class_name_prefix, self.options.class_name_suffix
+        )
+    }
+}
+
+/// Builds the error of `--fail-on-duplicate-column` for the first column that maps to the same property
+/// of the same class as an earlier one, naming both definitions, or `None` if there are no duplicates
+pub(crate) fn duplicate_column_error(
+    table_column_definitions: &[TableColumnDefinition],
+    options: &ConversionOptions,
+) -> Option<String> {
+    let class_namer = ClassNamer::new(table_column_definitions, options);
+    let describe = |column: &TableColumnDefinition| {
+        format!(
+            "`{}.{}` ({}, {})",
+            column.table_name,
+            column.column_name,
+            column.data_type,
+            if column.nullable {
+                "nullable"
+            } else {
+                "not null"
+            }
+        )
+    };
+
+    let mut seen = HashMap::<(String, String), &TableColumnDefinition>::new();
+    // in the same order as the conversion, so the first definition is the one that would be kept
+    for column in table_column_definitions
+        .iter()
+        .sorted_by(|a, b| a.table_name.cmp(&b.table_name))
+    {
+        let class_name = class_namer.class_name(column);
+        let column_name = options.identifier(&column.column_name);
+        if let Some(first) = seen.get(&(class_name.clone(), column_name.clone())) {
+            return Some(format!(
+                "Duplicate column `{class_name}.{column_name}`: {} conflicts with {}",
+                describe(column),
+                describe(first)
+            ));
+        }
+        seen.insert((class_name, column_name), column);
+    }
+    None
+}
+
 /// Converts a `Vec<TableColumnDefinition>` that comes from the database introspection query
 /// into the `Vec<PythonTypedDict>` that is easy to manipulate into a Python source file
+///
+/// Tables whose names map to the same class name, e.g. `Users` and `users` on MySQL with
+/// `lower_case_table_names=2`, are merged into a single class named after the first table alphabetically,
+/// with a warning, instead of being written as two classes with the same name. A column that repeats
+/// an earlier one of its class is skipped with a warning, see `duplicate_column_error` for `--fail-on-duplicate-column`
 pub(crate) fn convert_table_column_definitions_to_python_dicts(
     table_column_definitions: Vec<TableColumnDefinition>,
+    options: &ConversionOptions,
 ) -> Vec<PythonTypedDict> {
+    let class_namer = ClassNamer::new(&table_column_definitions, options);
+
     let mut tables_map = HashMap::<String, PythonTypedDict>::new();
-    for table_column_definition in table_column_definitions {
+    let mut merged_table_names = HashSet::<String>::new();
+    // a stable sort keeps the column order, but makes the merged class the same whichever table comes back first
+    for table_column_definition in table_column_definitions
+        .into_iter()
+        .sorted_by(|a, b| a.table_name.cmp(&b.table_name))
+    {
+        let class_name = class_namer.class_name(&table_column_definition);
         let dict = tables_map
-            .entry(table_column_definition.table_name.clone())
+            .entry(class_name.clone())
             .or_insert(PythonTypedDict {
-                name: table_column_definition.table_name.to_case(Case::Pascal),
+                name: class_name,
+                raw_name: table_column_definition.table_name.clone(),
                 properties: vec![],
             });
 
+        let column_name = options.identifier(&table_column_definition.column_name);
+        if dict.raw_name != table_column_definition.table_name {
+            if merged_table_names.insert(table_column_definition.table_name.clone()) {
+                eprintln!(
+                    "Warning: merging table `{}` into `{}` because both map to the class `{}`",
+                    table_column_definition.table_name, dict.raw_name, dict.name
+                );
+            }
+            if dict
+                .properties
+                .iter()
+                .any(|property| property.name == column_name)
+            {
+                continue;
+            }
+        } else if dict
+            .properties
+            .iter()
+            .any(|property| property.name == column_name)
+        {
+            if column_name == table_column_definition.column_name {
+                eprintln!(
+                    "Warning: skipping duplicate column `{}.{column_name}`, keeping its first definition",
+                    table_column_definition.table_name
+                );
+            } else {
+                eprintln!(
+                    "Warning: skipping column `{}` of `{}` because it is the same Python identifier as another column, `{column_name}`, after NFKC normalization",
+                    table_column_definition.column_name, table_column_definition.table_name
+                );
+            }
+            continue;
+        }
+
+        let is_tinyint = table_column_definition.data_type == "tinyint";
+        // MySQL's booleans are `tinyint(1)`, so a `column_type` of another width is a plain integer
+        let is_boolean_tinyint = is_tinyint
+            && table_column_definition
+                .column_type
+                .as_deref()
+                .is_none_or(|column_type| column_type == "tinyint(1)");
+        let data_type = options.column_data_type(&table_column_definition.data_type);
+        if !options.keeps_column_type(&data_type) {
+            continue;
+        }
+
+        // the database stores these booleans as the integers 0 and 1
+        let data_type = match (
+            is_tinyint && options.bool_as_int_literal,
+            is_boolean_tinyint,
+        ) {
+            (true, true) => PythonDataType::Literal(vec![String::from("0"), String::from("1")]),
+            (true, false) => PythonDataType::Integer,
+            (false, _) => data_type,
+        };
+
+        // columns sampled by `--infer-enums` with few enough distinct values become a `Literal[...]`
+        let data_type = table_column_definition
+            .distinct_values
+            .as_deref()
+            .and_then(|values| {
+                PythonDataType::literal_from_distinct_values(values, MAX_INFERRED_ENUM_VALUES)
+            })
+            .unwrap_or(data_type);
+
+        // the declared members of a MySQL or Postgres `enum` are exact, unlike sampled values
+        let data_type = match table_column_definition
+            .column_type
+            .as_deref()
+            .and_then(PythonDataType::literal_from_mysql_enum)
+        {
+            Some(literal) => data_type.with_element(literal),
+            None => data_type,
+        };
+
+        let assumed_not_null = options
+            .assume_not_null
+            .iter()
+            .any(|pattern| pattern.matches(&table_column_definition.column_name));
+
         dict.properties.push(PythonDictProperty {
-            name: table_column_definition.column_name,
-            nullable: table_column_definition.nullable,
-            data_type: table_column_definition.data_type.into(),
+            name: column_name,
+            nullable: table_column_definition.nullable && !assumed_not_null,
+            data_type,
+            comment: table_column_definition.comment.map(|comment| {
+                match options.comment_max_length {
+                    Some(max_length) => truncate_comment(comment, max_length),
+                    None => comment,
+                }
+            }),
+            max_length: table_column_definition.max_length,
+            check_bounds: table_column_definition.check_bounds,
+            primary_key: table_column_definition.primary_key,
         });
     }
 
+    // tables emptied by the column filters are reported by `emptied_table_warnings`
     tables_map
         .into_values()
+        .filter(|dict| !dict.properties.is_empty())
         .sorted_by_key(|d| d.name.clone())
         .collect()
 }
 
-/// Writes the `Vec<PythonTypedDict>` into a Python source string that can then later be written to a file inside `main()`
-pub(crate) fn write_python_dicts_to_str(
+/// How many columns have to share a prefix for `--group-columns-by-prefix` to group them
+const MIN_GROUPED_COLUMNS: usize = 2;
+
+/// Options that control how the Python source file is rendered
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WriterOptions {
+    pub(crate) minimum_python_version: MinimumPythonVersion,
+    pub(crate) output_style: OutputStyle,
+    pub(crate) dataclass_options: DataclassOptions,
+    /// Whether every key of every `TypedDict` is required, or may be left out with `total=False`
+    pub(crate) totality: Totality,
+    /// Writes `TypedDict`s with the backward-compatible syntax on a single line
+    /// if they have at most this many columns
+    pub(crate) compact_max_columns: Option<usize>,
+    /// How json columns are typed in the Pydantic output style, or `str` if `None`
+    pub(crate) pydantic_json: Option<PydanticJson>,
+    /// Emits structurally identical tables as aliases of the first one alphabetically
+    pub(crate) alias_identical: bool,
+    /// Records the introspected source in the header, e.g. `public@db.example.com`
+    pub(crate) generated_from: Option<String>,
+    /// Types every `datetime.datetime` field as the `AwareDatetime` alias of `Annotated[datetime.datetime, "UTC"]`,
+    /// even when the column is stored without a time zone
+    pub(crate) datetime_aware: bool,
+    /// Collapses the imports onto a single line so the output can be pasted into a notebook cell
+    pub(crate) single_cell: bool,
+    /// Leaves out the import block, so the definitions can be embedded into a file that provides the imports
+    pub(crate) no_imports: bool,
+    /// Writes an `__init__.pyi` stub with explicit re-exports alongside the `__init__.py` of a split package
+    pub(crate) init_stub: bool,
+    /// Adds a `__tablename__` class variable with the original table name to the classes
+    /// of the dataclass and pydantic styles
+    pub(crate) emit_tablename: bool,
+    /// Adds a concise `__repr__` method to the classes of the dataclass and pydantic styles
+    pub(crate) emit_repr: bool,
+    /// Renders these tables, by their database name, in a different style than `output_style`
+    pub(crate) style_overrides: Vec<(String, OutputStyle)>,
+    /// Writes tables with a single column as a type alias of that column's type instead of a class
+    pub(crate) flatten_single_column: bool,
+    /// Types each primary key column as its own `NewType`, e.g. `UsersId = NewType("UsersId", int)`
+    pub(crate) pk_newtypes: bool,
+    /// Groups columns that share a prefix, like `address_street` and `address_city`, into a nested class
+    pub(crate) group_columns_by_prefix: bool,
+    /// Adds an insert-shaped `<Class>Kwargs` `TypedDict` and a `create_<table>(**kwargs: Unpack[...])` stub per table
+    pub(crate) kwargs_variant: bool,
+    /// Applies a fixed ordering to the imports and properties, so that the output only changes with the schema
+    pub(crate) canonical: bool,
+    /// Whether the names used from `typing` are imported directly, through the module, or under an alias
+    pub(crate) typing_import_style: TypingImportStyle,
+    /// Whether the functional `TypedDict` syntax names each dict after its class or its table
+    pub(crate) typeddict_name_source: TypedDictNameSource,
+    /// Splits tables whose column of this name is a `Literal` into a class per value and a union of them
+    pub(crate) discriminator_column: Option<String>,
+    /// Adds a `make_<table>(...)` factory function after each `TypedDict`
+    pub(crate) emit_factory_functions: bool,
+    /// Adds a `MODELS` dict mapping each table name to its class at the end of the file
+    pub(crate) emit_registry: bool,
+    /// Adds a `RowJSONEncoder` class that serializes the date, decimal, and UUID values of the rows to JSON
+    pub(crate) emit_json_encoder: bool,
+    /// Adds a `# 12 columns` comment above each class
+    pub(crate) column_count_comment: bool,
+    /// Adds a `# keys: id, name, email` comment listing the column names above each class
+    pub(crate) keys_comment: bool,
+    /// Guards the imports and definitions with `if TYPE_CHECKING:`, so importing the module at runtime
+    /// costs almost nothing, for code that only uses the types in annotations
+    pub(crate) types_only_module: bool,
+    /// Skips stripping trailing whitespace and normalizing the output to end with a single newline
+    pub(crate) keep_trailing_whitespace: bool,
+}
+
+impl WriterOptions {
+    /// The output style of a single table, which is `output_style` unless the table is in `style_overrides`
+    fn output_style_for(&self, dict: &PythonTypedDict) -> OutputStyle {
+        self.style_overrides
+            .iter()
+            .find(|(table_name, _)| {
+                // nested dicts from `--group-columns-by-prefix` share the `raw_name` of their table
+                table_name.to_case(Case::Pascal) == dict.raw_name.to_case(Case::Pascal)
+            })
+            .map(|(_, style)| *style)
+            .unwrap_or(self.output_style)
+    }
+
+    /// Whether a table is written as a bare type alias because of `flatten_single_column`
+    fn is_flattened(&self, dict: &PythonTypedDict) -> bool {
+        self.flatten_single_column
+            && dict.properties.len() == 1
+            && self.output_style_for(dict) != OutputStyle::Polars
+    }
+
+    /// The name a table is defined as, which is its schema dict rather than a class with `--output-style polars`
+    fn exported_name(&self, dict: &PythonTypedDict) -> String {
+        match self.output_style_for(dict) {
+            OutputStyle::Polars => as_polars_schema_name(&dict.name),
+            _ => dict.name.clone(),
+        }
+    }
+}
+
+/// Whether a `PythonTypedDict` can be written out with a valid Python class name
+pub(crate) fn is_writable_dict(dict: &PythonTypedDict) -> bool {
+    !dict.name.contains('$') // prevents weirdness with some system tables
+        && !dict.name.chars().next().unwrap().is_numeric()
+}
+
+/// Whether SQLAlchemy would refuse to map a `PythonTypedDict`, since it has no primary key
+fn lacks_sqlalchemy_primary_key(dict: &PythonTypedDict, options: &WriterOptions) -> bool {
+    options.output_style_for(dict) == OutputStyle::SqlAlchemy
+        && !dict.properties.iter().any(|property| property.primary_key)
+}
+
+/// How many of the `dicts` `write_python_dicts_to_str` writes a definition for, for the `--dry-run` summary
+pub(crate) fn count_written_dicts(dicts: &[PythonTypedDict], options: &WriterOptions) -> usize {
+    dicts
+        .iter()
+        .filter(|dict| is_writable_dict(dict) && !lacks_sqlalchemy_primary_key(dict, options))
+        .count()
+}
+
+/// Writes the `Vec<PythonTypedDict>` into a Python package with one module per table, an `__init__.py`
+/// that re-exports every type, and a `py.typed` marker, so that it can be written to a directory or archive.
+///
+/// With `options.init_stub`, an `__init__.pyi` stub with explicit re-exports is written too
+pub(crate) fn write_python_dicts_to_files(
     dicts: Vec<PythonTypedDict>,
-    minimum_python_version: MinimumPythonVersion,
-) -> String {
-    let mut result = formatdoc! {"
+    options: &WriterOptions,
+) -> Vec<GeneratedFile> {
+    let module_dicts = dicts
+        .into_iter()
+        .filter(is_writable_dict)
+        .sorted_by_key(|dict| dict.name.clone())
+        .map(|dict| (dict.name.to_case(Case::Snake), dict))
+        .collect::<Vec<(String, PythonTypedDict)>>();
+
+    // the registry of a package is in its `__init__.py`, which imports every class
+    let registry = options
+        .emit_registry
+        .then(|| {
+            as_registry_str(
+                &module_dicts
+                    .iter()
+                    .map(|(_, dict)| dict.clone())
+                    .collect::<Vec<PythonTypedDict>>(),
+                options,
+            )
+        })
+        .flatten();
+    let module_options = WriterOptions {
+        emit_registry: false,
+        ..options.clone()
+    };
+
+    let mut init_contents = formatdoc! {"
         # This file was generated by db-introspector-gadget
         # https://github.com/sesgoe/db-introspector-gadget
 
-        # This file requires Python >= {}
-        # If this is in error, please check the --minimum-python-version (-p) argument
+    "};
+    // the redundant `as` marks each import as an explicit re-export for strict type checkers
+    let mut init_stub_contents = init_contents.clone();
+    if registry.is_some() && options.minimum_python_version < MinimumPythonVersion::Python3_10 {
+        init_contents.push_str("from typing import Dict\n\n");
+    }
+    for (module_name, dict) in &module_dicts {
+        let name = options.exported_name(dict);
+        init_contents.push_str(format!("from .{} import {}\n", module_name, name).as_str());
+        init_stub_contents
+            .push_str(format!("from .{} import {} as {}\n", module_name, name, name).as_str());
+    }
+    init_contents.push_str(
+        format!(
+            "\n__all__ = [{}]\n",
+            module_dicts
+                .iter()
+                .map(|(_, dict)| format!("\"{}\"", options.exported_name(dict)))
+                .join(", ")
+        )
+        .as_str(),
+    );
+    if let Some(registry) = registry {
+        init_contents.push_str(&format!("\n{registry}"));
+    }
 
-    ",
-        match minimum_python_version {
-            MinimumPythonVersion::Python3_10 => "3.10",
-            MinimumPythonVersion::Python3_8 => "3.8",
-            MinimumPythonVersion::Python3_6 => "3.6"
-        }
-    };
+    let mut files = module_dicts
+        .into_iter()
+        .map(|(module_name, dict)| GeneratedFile {
+            path: format!("{module_name}.py"),
+            contents: write_python_dicts_to_str(vec![dict], &module_options),
+        })
+        .collect::<Vec<GeneratedFile>>();
 
-    result.push_str(match minimum_python_version {
-        MinimumPythonVersion::Python3_10 => {
-            "import datetime\nfrom typing import Any, TypedDict\n\n\n" // no Optional in Python 3.10
-        }
-        _ => "import datetime\nfrom typing import Any, Optional, TypedDict\n\n\n",
+    files.push(GeneratedFile {
+        path: String::from("__init__.py"),
+        contents: init_contents,
     });
+    if options.init_stub {
+        files.push(GeneratedFile {
+            path: String::from("__init__.pyi"),
+            contents: init_stub_contents,
+        });
+    }
+    files.push(GeneratedFile {
+        path: String::from("py.typed"),
+        contents: String::new(),
+    });
+
+    files
+}
+
+/// Whether any property of the dict isn't a valid Python identifier, so that it can't be written as a class attribute
+fn requires_backward_compat(dict: &PythonTypedDict) -> bool {
+    let starts_with_number = |p: &PythonDictProperty| p.name.chars().next().unwrap().is_numeric();
+    let contains_space = |p: &PythonDictProperty| p.name.contains(' ');
+    let is_python_keyword = |p: &PythonDictProperty| p.name == "from";
 
-    let python_dicts_str = dicts
+    dict.properties
         .iter()
-        .filter(|dict| !dict.name.contains('$')) // prevents weirdness with some system tables
-        .filter(|dict| !dict.name.chars().next().unwrap().is_numeric())
-        .sorted_by_key(|f| f.name.clone())
-        .map(|dict| {
-            let mut iter = dict.properties.iter();
+        .any(|p| starts_with_number(p) || contains_space(p) || is_python_keyword(p))
+}
+
+/// Writes an insert-shaped `<Class>Kwargs` `TypedDict`, where nullable columns may be left out,
+/// and a `create_<table>` function stub that accepts it as keyword arguments through `Unpack`
+fn as_kwargs_variant_str(
+    dict: &PythonTypedDict,
+    minimum_python_version: MinimumPythonVersion,
+) -> String {
+    let kwargs_name = format!("{}Kwargs", dict.name);
+    let mut result = format!("class {kwargs_name}(TypedDict):\n");
+    for property in &dict.properties {
+        let type_str = property.as_property_type_str(minimum_python_version);
+        if property.nullable {
+            result.push_str(&format!(
+                "    {}: NotRequired[{}]\n",
+                property.name, type_str
+            ));
+        } else {
+            result.push_str(&format!("    {}: {}\n", property.name, type_str));
+        }
+    }
+    result.push_str(&formatdoc! {"
 
-            let starts_with_number =
-                |p: &PythonDictProperty| p.name.chars().next().unwrap().is_numeric();
-            let contains_space = |p: &PythonDictProperty| p.name.contains(' ');
-            let is_python_keyword = |p: &PythonDictProperty| p.name == "from";
 
-            let requires_backward_compat =
-                iter.any(|p| starts_with_number(p) || contains_space(p) || is_python_keyword(p));
+        def create_{}(**kwargs: Unpack[{kwargs_name}]) -> None: ...
+    ", dict.name.to_case(Case::Snake)});
+    result
+}
 
-            dict.as_typed_dict_class_str(minimum_python_version, requires_backward_compat.into())
+/// Writes a `make_<table>` function that builds the `TypedDict` from its columns as arguments,
+/// where nullable columns default to `None` and so come after the others
+fn as_factory_function_str(
+    dict: &PythonTypedDict,
+    minimum_python_version: MinimumPythonVersion,
+) -> String {
+    let parameters = dict
+        .class_fields()
+        .map(|(property, default)| {
+            let type_str = property.as_property_type_str(minimum_python_version);
+            match default {
+                Some(default) => format!("{}: {} = {}", property.name, type_str, default),
+                None => format!("{}: {}", property.name, type_str),
+            }
         })
-        .collect::<Vec<String>>()
-        .join("\n\n");
+        .join(", ");
+    let arguments = dict
+        .properties
+        .iter()
+        .map(|property| format!("{}={}", property.name, property.name))
+        .join(", ");
 
-    result.push_str(python_dicts_str.as_str());
+    formatdoc! {"
+        def make_{}({parameters}) -> {}:
+            return {}({arguments})
+    ", dict.name.to_case(Case::Snake), dict.name, dict.name}
+}
+
+/// Writes the `--emit-json-encoder` class, which handles exactly the types of the given properties
+/// that `json.dumps` can't serialize: dates and times as ISO 8601 strings, and decimals and UUIDs as strings
+fn as_json_encoder_str<'a>(
+    properties: impl Iterator<Item = &'a PythonDictProperty> + Clone,
+) -> String {
+    let uses = |data_type: PythonDataType| {
+        properties
+            .clone()
+            .any(|property| property.data_type.uses(&data_type))
+    };
 
+    let mut result = formatdoc! {"
+        class RowJSONEncoder(json.JSONEncoder):
+            def default(self, o: Any) -> Any:
+    "};
+    let date_types = [
+        (PythonDataType::DateTime, "datetime.datetime"),
+        (PythonDataType::Date, "datetime.date"),
+        (PythonDataType::Time, "datetime.time"),
+    ]
+    .into_iter()
+    .filter(|(data_type, _)| uses(data_type.clone()))
+    .map(|(_, type_str)| type_str)
+    .collect::<Vec<&str>>();
+    match date_types.as_slice() {
+        [] => {}
+        [date_type] => result.push_str(&format!(
+            "        if isinstance(o, {date_type}):\n            return o.isoformat()\n"
+        )),
+        _ => result.push_str(&format!(
+            "        if isinstance(o, ({})):\n            return o.isoformat()\n",
+            date_types.join(", ")
+        )),
+    }
+    if uses(PythonDataType::Interval) {
+        result.push_str(
+            "        if isinstance(o, datetime.timedelta):\n            return o.total_seconds()\n",
+        );
+    }
+    if uses(PythonDataType::Decimal) {
+        result.push_str("        if isinstance(o, decimal.Decimal):\n            return str(o)\n");
+    }
+    if uses(PythonDataType::Uuid) {
+        result.push_str("        if isinstance(o, uuid.UUID):\n            return str(o)\n");
+    }
+    result.push_str("        return super().default(o)\n");
     result
 }
 
-#[cfg(test)]
-mod test {
-    use crate::python_types::PythonDataType;
-    use indoc::indoc;
+/// Writes the `--emit-registry` dict that maps each table name to its generated class, or `None` if there are none.
+/// Polars schema dicts aren't classes, so they are left out
+fn as_registry_str(dicts: &[PythonTypedDict], options: &WriterOptions) -> Option<String> {
+    let mut entries = vec![];
+    for dict in dicts
+        .iter()
+        .filter(|dict| {
+            is_writable_dict(dict) && options.output_style_for(dict) != OutputStyle::Polars
+        })
+        .sorted_by(|a, b| (&a.raw_name, &a.name).cmp(&(&b.raw_name, &b.name)))
+    {
+        // e.g. same-named tables of different schemas
+        if entries
+            .iter()
+            .any(|(raw_name, _)| *raw_name == &dict.raw_name)
+        {
+            eprintln!(
+                "Warning: leaving `{}` out of the registry because the table name `{}` is already registered",
+                dict.name, dict.raw_name
+            );
+            continue;
+        }
+        entries.push((&dict.raw_name, &dict.name));
+    }
+    if entries.is_empty() {
+        return None;
+    }
 
-    use super::*;
+    let dict_type = if options.minimum_python_version >= MinimumPythonVersion::Python3_10 {
+        "dict[str, type]"
+    } else {
+        "Dict[str, type]"
+    };
+    let entries = entries
+        .into_iter()
+        .map(|(raw_name, name)| format!("    {}: {},\n", as_python_string_literal(raw_name), name))
+        .collect::<String>();
+    Some(format!("MODELS: {dict_type} = {{\n{entries}}}\n"))
+}
 
-    #[test]
-    fn convert_definitions_to_single_dict_for_single_table() {
-        let table_column_definitions = vec![
-            TableColumnDefinition {
-                table_name: String::from("some_table"),
-                column_name: String::from("column_one"),
-                nullable: false,
-                data_type: String::from("varchar"),
-            },
-            TableColumnDefinition {
-                table_name: String::from("some_table"),
-                column_name: String::from("column_two"),
-                nullable: true,
-                data_type: String::from("varchar"),
-            },
-        ];
+/// Writes a `# 12 columns` comment, to make the size of each class easy to see while scanning large files
+fn as_column_count_comment(dict: &PythonTypedDict) -> String {
+    match dict.properties.len() {
+        1 => String::from("# 1 column"),
+        count => format!("# {count} columns"),
+    }
+}
+
+/// Writes a `# keys: id, name, email` comment with the column names in the order of the class, as a quick reference
+/// of the expected keys
+fn as_keys_comment(dict: &PythonTypedDict) -> String {
+    format!(
+        "# keys: {}",
+        dict.properties
+            .iter()
+            .map(|property| &property.name)
+            .join(", ")
+    )
+}
 
-        let result = convert_table_column_definitions_to_python_dicts(table_column_definitions);
+/// Writes a single `TypedDict`, falling back to the backward-compatible syntax if any property
+/// can't be written as a class attribute
+fn as_typed_dict_str(dict: &PythonTypedDict, options: &WriterOptions) -> String {
+    let minimum_python_version = options.minimum_python_version;
+    let requires_backward_compat = requires_backward_compat(dict);
 
-        let expected = vec![PythonTypedDict {
-            name: String::from("SomeTable"),
-            properties: vec![
-                PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                },
-                PythonDictProperty {
-                    name: String::from("column_two"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                },
-            ],
-        }];
+    let use_alternate_syntax =
+        requires_backward_compat || minimum_python_version == MinimumPythonVersion::Python3_6;
+    let is_compact = options
+        .compact_max_columns
+        .is_some_and(|max_columns| dict.properties.len() <= max_columns);
 
-        assert_eq!(result, expected)
+    if use_alternate_syntax && is_compact {
+        return dict.as_compact_typed_dict_str(
+            minimum_python_version,
+            options.totality,
+            options.typeddict_name_source,
+        );
     }
 
-    #[test]
-    fn convert_definitions_to_two_dicts_for_two_tables() {
-        let table_column_definitions = vec![
-            TableColumnDefinition {
-                table_name: String::from("some_other_table"),
-                column_name: String::from("column_one"),
-                nullable: true,
-                data_type: String::from("varchar"),
-            },
-            TableColumnDefinition {
-                table_name: String::from("some_table"),
-                column_name: String::from("column_one"),
-                nullable: false,
-                data_type: String::from("varchar"),
-            },
-        ];
+    dict.as_typed_dict_class_str(
+        minimum_python_version,
+        requires_backward_compat.into(),
+        options.totality,
+        options.typeddict_name_source,
+    )
+}
 
-        let result = convert_table_column_definitions_to_python_dicts(table_column_definitions);
+/// Replaces the type of each primary key column with a `NewType` named `<Class><Column>`,
+/// so that e.g. an order's ID can't be passed where a user's ID is expected
+fn with_primary_key_new_types(mut dict: PythonTypedDict) -> PythonTypedDict {
+    for property in dict.properties.iter_mut() {
+        if property.primary_key && property.data_type.supports_new_type() {
+            property.data_type = PythonDataType::NewType {
+                name: format!("{}{}", dict.name, property.name.to_case(Case::Pascal)),
+                base: Box::new(property.data_type.clone()),
+            };
+        }
+    }
+    dict
+}
 
-        let expected = vec![
-            PythonTypedDict {
-                name: String::from("SomeOtherTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
-            },
-            PythonTypedDict {
-                name: String::from("SomeTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
+/// Groups columns that share a prefix, like `address_street`, `address_city`, and `address_zip`,
+/// into a nested `<Class><Prefix>` dict referenced from an `address` property of the parent.
+///
+/// The nested dicts are returned ahead of the parent. Primary key columns are never grouped,
+/// and neither is a prefix that is already a column name of its own
+fn with_columns_grouped_by_prefix(dict: PythonTypedDict) -> Vec<PythonTypedDict> {
+    let prefix_of = |property: &PythonDictProperty| match property.name.split_once('_') {
+        Some((prefix, rest)) if !prefix.is_empty() && !rest.is_empty() && !property.primary_key => {
+            Some(prefix.to_string())
+        }
+        _ => None,
+    };
+
+    let prefix_counts = dict.properties.iter().filter_map(prefix_of).counts();
+    let grouped_prefix = |property: &PythonDictProperty| {
+        prefix_of(property).filter(|prefix| {
+            prefix_counts[prefix] >= MIN_GROUPED_COLUMNS
+                && !dict.properties.iter().any(|other| &other.name == prefix)
+        })
+    };
+
+    let mut nested_dicts = Vec::<PythonTypedDict>::new();
+    let mut properties = Vec::<PythonDictProperty>::new();
+    for property in &dict.properties {
+        let Some(prefix) = grouped_prefix(property) else {
+            properties.push(property.clone());
+            continue;
+        };
+
+        let nested_name = format!("{}{}", dict.name, prefix.to_case(Case::Pascal));
+        let nested_property = PythonDictProperty {
+            name: property.name[prefix.len() + 1..].to_string(),
+            ..property.clone()
+        };
+        match nested_dicts
+            .iter_mut()
+            .find(|nested| nested.name == nested_name)
+        {
+            Some(nested) => nested.properties.push(nested_property),
+            None => {
+                properties.push(PythonDictProperty {
+                    name: prefix,
                     nullable: false,
-                    data_type: PythonDataType::String,
-                }],
-            },
-        ];
+                    data_type: PythonDataType::Nested(nested_name.clone()),
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                });
+                nested_dicts.push(PythonTypedDict {
+                    name: nested_name,
+                    raw_name: dict.raw_name.clone(),
+                    properties: vec![nested_property],
+                });
+            }
+        }
+    }
 
-        assert_eq!(result, expected)
+    nested_dicts.push(PythonTypedDict { properties, ..dict });
+    nested_dicts
+}
+
+/// Orders the dicts so that the nested dicts a dict references are written before it,
+/// since a class annotation can't refer to a class that is defined later
+fn nested_dicts_first(dicts: Vec<&PythonTypedDict>) -> Vec<&PythonTypedDict> {
+    let mut ordered = Vec::<&PythonTypedDict>::new();
+    for dict in &dicts {
+        for property in &dict.properties {
+            let PythonDataType::Nested(nested_name) = &property.data_type else {
+                continue;
+            };
+            if let Some(nested) = dicts.iter().find(|nested| &nested.name == nested_name) {
+                if !ordered.iter().any(|other| other.name == nested.name) {
+                    ordered.push(nested);
+                }
+            }
+        }
+        if !ordered.iter().any(|other| other.name == dict.name) {
+            ordered.push(dict);
+        }
     }
+    ordered
+}
 
-    #[test]
-    fn sorts_python_dict_vec_correctly() {
-        let table_column_definitions = vec![
-            TableColumnDefinition {
-                table_name: String::from("b_table"),
-                column_name: String::from("column_one"),
-                nullable: false,
-                data_type: String::from("varchar"),
-            },
-            TableColumnDefinition {
-                table_name: String::from("a_table"),
-                column_name: String::from("column_one"),
+impl TypingImportStyle {
+    /// Builds the line that imports the given names from `typing`
+    fn as_import_line(&self, names: &[&str]) -> String {
+        match self {
+            TypingImportStyle::From => format!("from typing import {}", names.join(", ")),
+            TypingImportStyle::Qualified => String::from("import typing"),
+            TypingImportStyle::Aliased => format!(
+                "from typing import {}",
+                names
+                    .iter()
+                    .map(|name| format!("{name} as {}", self.as_name(name)))
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// How a name imported from `typing` is referred to
+    fn as_name(&self, name: &str) -> String {
+        match self {
+            TypingImportStyle::From => name.to_string(),
+            TypingImportStyle::Qualified => format!("typing.{name}"),
+            TypingImportStyle::Aliased => format!("_{name}"),
+        }
+    }
+}
+
+/// Renames the references to the given `typing` names in generated definitions according to `typing_import_style`.
+///
+/// Only references are renamed: names inside strings and comments, attributes like `datetime.datetime`,
+/// and the names being defined (the first identifier of a line, and the one after `class`, `def`, or `type`)
+/// are left alone, so a column or table that happens to be named e.g. `Any` keeps its name
+fn with_typing_names_renamed(
+    source: &str,
+    typing_names: &[&str],
+    typing_import_style: TypingImportStyle,
+) -> String {
+    let mut result = String::with_capacity(source.len());
+    for line in source.split_inclusive('\n') {
+        let chars = line.chars().collect::<Vec<char>>();
+        let mut string_quote: Option<char> = None;
+        let mut is_first_identifier = true;
+        let mut previous_identifier = String::new();
+        let mut previous_char = ' ';
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if let Some(quote) = string_quote {
+                result.push(c);
+                if c == '\\' && i + 1 < chars.len() {
+                    result.push(chars[i + 1]);
+                    i += 1;
+                } else if c == quote {
+                    string_quote = None;
+                }
+                i += 1;
+                continue;
+            }
+
+            if c == '#' {
+                result.extend(&chars[i..]);
+                break;
+            }
+
+            if c.is_alphabetic() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let identifier = chars[start..i].iter().collect::<String>();
+                let is_reference = !is_first_identifier
+                    && previous_char != '.'
+                    && !matches!(previous_identifier.as_str(), "class" | "def" | "type");
+                if is_reference && typing_names.contains(&identifier.as_str()) {
+                    result.push_str(&typing_import_style.as_name(&identifier));
+                } else {
+                    result.push_str(&identifier);
+                }
+                is_first_identifier = false;
+                previous_identifier = identifier;
+                previous_char = chars[i - 1];
+                continue;
+            }
+
+            if c == '"' || c == '\'' {
+                string_quote = Some(c);
+            }
+            if !c.is_whitespace() {
+                is_first_identifier = false;
+                previous_identifier.clear();
+                previous_char = c;
+            }
+            result.push(c);
+            i += 1;
+        }
+    }
+    result
+}
+
+/// The modules of the standard library that the generated imports can come from
+const STANDARD_LIBRARY_MODULES: &[&str] = &[
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "json",
+    "typing",
+    "uuid",
+];
+
+/// Orders the import lines like isort's defaults: a section of standard library imports, then a section of
+/// third-party imports separated by an empty line, with plain `import`s ahead of `from` imports, modules
+/// sorted alphabetically, and the imported names sorted as constants, then classes, then functions
+fn as_canonical_import_lines(import_lines: Vec<String>) -> Vec<String> {
+    let name_order = |name: &str| {
+        let kind = if name.len() > 1 && name.chars().all(|c| !c.is_lowercase()) {
+            0 // CONSTANT
+        } else if name.starts_with(char::is_uppercase) {
+            1 // Class
+        } else {
+            2 // function
+        };
+        (kind, name.to_lowercase())
+    };
+
+    let imports = import_lines
+        .iter()
+        .map(|line| match line.strip_prefix("from ") {
+            Some(from_import) => {
+                let (module, names) = from_import.split_once(" import ").unwrap_or_default();
+                let names = names
+                    .split(", ")
+                    .sorted_by_key(|name| name_order(name))
+                    .join(", ");
+                (module, Some(names))
+            }
+            None => (line.trim_start_matches("import "), None),
+        })
+        .sorted_by_key(|(module, names)| {
+            (
+                !STANDARD_LIBRARY_MODULES.contains(module),
+                names.is_some(),
+                module.to_string(),
+            )
+        })
+        .collect::<Vec<(&str, Option<String>)>>();
+
+    let mut result = vec![];
+    for (position, (module, names)) in imports.iter().enumerate() {
+        let is_first_third_party = !STANDARD_LIBRARY_MODULES.contains(module)
+            && (position == 0 || STANDARD_LIBRARY_MODULES.contains(&imports[position - 1].0));
+        if is_first_third_party && position > 0 {
+            result.push(String::new());
+        }
+        result.push(match names {
+            Some(names) => format!("from {module} import {names}"),
+            None => format!("import {module}"),
+        });
+    }
+    result
+}
+
+/// A union of the per-value variants of a table whose discriminator column is a `Literal`
+struct DiscriminatedUnion {
+    name: String,
+    discriminator_column: String,
+    variants: Vec<String>,
+}
+
+/// Adds a `<Class><Value>` variant of the dict per value of its `discriminator_column`, with that column narrowed
+/// to the single value, and returns the union of the variants.
+///
+/// Dicts without a `Literal` discriminator column, or with a value that can't be part of a class name, are left
+/// as they are. The variants only differ in the discriminator column, since the database can't tell which
+/// columns belong to which value
+fn with_discriminated_variants(
+    dict: PythonTypedDict,
+    discriminator_column: &str,
+) -> (Vec<PythonTypedDict>, Option<DiscriminatedUnion>) {
+    let Some((position, values)) =
+        dict.properties
+            .iter()
+            .enumerate()
+            .find_map(|(position, property)| match &property.data_type {
+                PythonDataType::Literal(values)
+                    if property.name == discriminator_column && values.len() > 1 =>
+                {
+                    Some((position, values.clone()))
+                }
+                _ => None,
+            })
+    else {
+        return (vec![dict], None);
+    };
+
+    // the values are Python string literals, e.g. `"click"` becomes `EventsClick`
+    let variant_names = values
+        .iter()
+        .map(|value| {
+            let suffix = value.trim_matches('"').to_case(Case::Pascal);
+            let is_valid = suffix.starts_with(char::is_alphabetic)
+                && suffix.chars().all(char::is_alphanumeric);
+            is_valid.then(|| format!("{}{}", dict.name, suffix))
+        })
+        .collect::<Option<Vec<String>>>();
+    let Some(variant_names) = variant_names.filter(|names| names.iter().all_unique()) else {
+        return (vec![dict], None);
+    };
+
+    let mut dicts = vec![];
+    for (value, variant_name) in values.iter().zip(&variant_names) {
+        let mut variant = PythonTypedDict {
+            name: variant_name.clone(),
+            ..dict.clone()
+        };
+        variant.properties[position].data_type = PythonDataType::Literal(vec![value.clone()]);
+        dicts.push(variant);
+    }
+    let union = DiscriminatedUnion {
+        name: format!("{}Variant", dict.name),
+        discriminator_column: discriminator_column.to_string(),
+        variants: variant_names,
+    };
+    dicts.insert(0, dict);
+    (dicts, Some(union))
+}
+
+/// Writes `alias` as a type alias of `target`, annotated where the Python version has a syntax for it
+/// so that it is unambiguous to type checkers
+fn as_type_alias_str(
+    alias: &str,
+    target: &str,
+    minimum_python_version: MinimumPythonVersion,
+) -> String {
+    match minimum_python_version {
+        MinimumPythonVersion::Python3_12 => format!("type {} = {}\n", alias, target),
+        MinimumPythonVersion::Python3_10 => format!("{}: TypeAlias = {}\n", alias, target),
+        _ => format!("{} = {}\n", alias, target),
+    }
+}
+
+/// Inserts an attribute line as the first line of the body of a class, right after its `class ...:` line
+fn insert_class_attribute(class_str: &str, attribute_line: &str) -> String {
+    let mut result = String::new();
+    let mut inserted = false;
+    for line in class_str.lines() {
+        result.push_str(line);
+        result.push('\n');
+        if !inserted && line.starts_with("class ") {
+            result.push_str(attribute_line);
+            result.push('\n');
+            inserted = true;
+        }
+    }
+    result
+}
+
+/// Writes the `Vec<PythonTypedDict>` into a Python source string that can then later be written to a file inside `main()`
+pub(crate) fn write_python_dicts_to_str(
+    dicts: Vec<PythonTypedDict>,
+    options: &WriterOptions,
+) -> String {
+    let minimum_python_version = options.minimum_python_version;
+
+    // SQLAlchemy refuses to map a table without a primary key
+    let dicts = dicts
+        .into_iter()
+        .filter(|dict| {
+            let is_mappable = !lacks_sqlalchemy_primary_key(dict, options);
+            if !is_mappable {
+                eprintln!(
+                    "Warning: skipping table `{}` because SQLAlchemy can't map a table without a primary key",
+                    dict.raw_name
+                );
+            }
+            is_mappable
+        })
+        .collect::<Vec<PythonTypedDict>>();
+
+    // taken before nested dicts and variants are added, which share the table name of the class they come from
+    let registry = options
+        .emit_registry
+        .then(|| as_registry_str(&dicts, options))
+        .flatten();
+
+    // classes are always sorted by name, so only the properties depend on the input order
+    let dicts = if options.canonical {
+        dicts
+            .into_iter()
+            .map(|mut dict| {
+                dict.properties.sort_by(|a, b| a.name.cmp(&b.name));
+                dict
+            })
+            .collect()
+    } else {
+        dicts
+    };
+    let dicts = if options.group_columns_by_prefix {
+        dicts
+            .into_iter()
+            .flat_map(with_columns_grouped_by_prefix)
+            .collect()
+    } else {
+        dicts
+    };
+    // before the `NewType`s, which can't be declared over an alias
+    let dicts = if options.datetime_aware {
+        dicts
+            .into_iter()
+            .map(|mut dict| {
+                for property in dict.properties.iter_mut() {
+                    property.data_type = property.data_type.clone().with_aware_datetimes();
+                }
+                dict
+            })
+            .collect()
+    } else {
+        dicts
+    };
+    let dicts = if options.pk_newtypes {
+        dicts.into_iter().map(with_primary_key_new_types).collect()
+    } else {
+        dicts
+    };
+    // after the `NewType`s, so that the variants of a table share them
+    let mut discriminated_unions = Vec::<DiscriminatedUnion>::new();
+    let dicts = match &options.discriminator_column {
+        Some(discriminator_column) => dicts
+            .into_iter()
+            .flat_map(|dict| {
+                // a schema dict describes the column dtypes, which the variants share
+                if options.output_style_for(&dict) == OutputStyle::Polars {
+                    return vec![dict];
+                }
+                let (dicts, union) = with_discriminated_variants(dict, discriminator_column);
+                discriminated_unions.extend(union);
+                dicts
+            })
+            .collect(),
+        None => dicts,
+    };
+
+    let mut result = String::from(indoc! {"
+        # This file was generated by db-introspector-gadget
+        # https://github.com/sesgoe/db-introspector-gadget
+    "});
+
+    if let Some(generated_from) = &options.generated_from {
+        result.push_str(format!("# generated-from: {generated_from}\n").as_str());
+    }
+
+    let minimum_python_version_str = match minimum_python_version {
+        MinimumPythonVersion::Python3_12 => "3.12",
+        MinimumPythonVersion::Python3_10 => "3.10",
+        MinimumPythonVersion::Python3_8 => "3.8",
+        MinimumPythonVersion::Python3_6 => "3.6",
+    };
+
+    result.push_str(&formatdoc! {"
+
+        # This file requires Python >= {minimum_python_version_str}
+        # If this is in error, please check the --minimum-python-version (-p) argument
+
+    "});
+
+    if options.datetime_aware {
+        result.push_str(indoc! {"
+            # All datetime.datetime fields are typed as timezone-aware,
+            # including columns stored without a time zone, whose values are in UTC
+
+        "});
+    }
+
+    let mut canonical_dicts = Vec::<&PythonTypedDict>::new();
+    let mut aliases = Vec::<(&str, &str)>::new();
+
+    // aliases are written last, but a nested dict has to be defined before the dict that references it
+    let nested_names = dicts
+        .iter()
+        .flat_map(|dict| &dict.properties)
+        .filter_map(|property| match &property.data_type {
+            PythonDataType::Nested(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<&str>>();
+
+    for dict in dicts
+        .iter()
+        .filter(|dict| is_writable_dict(dict))
+        .sorted_by_key(|f| f.name.clone())
+    {
+        // dicts are sorted by name, so the canonical dict is always the first alphabetically
+        // an alias would lose its own `__tablename__`, so classes that have one are never aliased
+        let has_tablename = (options.emit_tablename
+            && options.output_style_for(dict) != OutputStyle::TypedDict)
+            || options.output_style_for(dict) == OutputStyle::SqlAlchemy;
+        let identical_dict = canonical_dicts.iter().find(|canonical| {
+            canonical.properties == dict.properties
+                && options.output_style_for(canonical) == options.output_style_for(dict)
+                && !has_tablename
+                && options.output_style_for(dict) != OutputStyle::Polars
+                && !nested_names.contains(&dict.name.as_str())
+        });
+
+        match identical_dict {
+            Some(canonical) if options.alias_identical => {
+                aliases.push((dict.name.as_str(), canonical.name.as_str()))
+            }
+            _ => canonical_dicts.push(dict),
+        }
+    }
+    let canonical_dicts = nested_dicts_first(canonical_dicts);
+
+    // keyword arguments have to be valid identifiers, and flattened and nested dicts aren't argument bundles
+    let has_kwargs_variant = |dict: &PythonTypedDict| {
+        options.kwargs_variant
+            && !options.is_flattened(dict)
+            && options.output_style_for(dict) != OutputStyle::Polars
+            && !nested_names.contains(&dict.name.as_str())
+            && !requires_backward_compat(dict)
+    };
+
+    // only what the definitions use is imported, so that the output passes strict linters
+    let typed_properties = dicts
+        .iter()
+        .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+        .flat_map(|dict| &dict.properties);
+    let has_pydantic_json_property = options.pydantic_json.is_some()
+        && dicts
+            .iter()
+            .filter(|dict| {
+                !options.is_flattened(dict)
+                    && options.output_style_for(dict) == OutputStyle::Pydantic
+            })
+            .flat_map(|dict| &dict.properties)
+            .any(|property| property.data_type == PythonDataType::Json);
+    let uses_any = typed_properties
+        .clone()
+        .any(|property| property.data_type.uses(&PythonDataType::Any))
+        || has_pydantic_json_property // `Json[Any]` or `dict[str, Any]`
+        || options.emit_json_encoder;
+
+    let mut typing_imports = vec![];
+    if uses_any {
+        typing_imports.push("Any");
+    }
+    if minimum_python_version < MinimumPythonVersion::Python3_10
+        && typed_properties.clone().any(|property| property.nullable)
+    {
+        typing_imports.push("Optional"); // no Optional in Python >= 3.10
+    }
+
+    let has_literal_property = dicts
+        .iter()
+        .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+        .flat_map(|dict| &dict.properties)
+        .any(|property| matches!(property.data_type, PythonDataType::Literal(_)));
+    if has_literal_property {
+        typing_imports.push("Literal");
+    }
+    let has_list_property = dicts
+        .iter()
+        .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+        .flat_map(|dict| &dict.properties)
+        .any(|property| matches!(property.data_type, PythonDataType::List(_)));
+    if has_list_property && minimum_python_version < MinimumPythonVersion::Python3_10 {
+        typing_imports.push("List"); // `list` isn't subscriptable before Python 3.9
+    }
+
+    let uses_style = |style: OutputStyle| {
+        (dicts.is_empty() && options.output_style == style)
+            || dicts
+                .iter()
+                .any(|dict| !options.is_flattened(dict) && options.output_style_for(dict) == style)
+    };
+
+    let mut import_lines = vec![];
+    let has_date_property = typed_properties.clone().any(|property| {
+        property.data_type.uses(&PythonDataType::DateTime)
+            || property.data_type.uses(&PythonDataType::Date)
+            || property.data_type.uses(&PythonDataType::Time)
+            || property.data_type.uses(&PythonDataType::Interval)
+    });
+    let has_decimal_property = typed_properties
+        .clone()
+        .any(|property| property.data_type.uses(&PythonDataType::Decimal));
+    let has_uuid_property = typed_properties
+        .clone()
+        .any(|property| property.data_type.uses(&PythonDataType::Uuid));
+    if has_date_property {
+        import_lines.push(String::from("import datetime"));
+    }
+    if has_decimal_property {
+        import_lines.push(String::from("import decimal"));
+    }
+    if options.emit_json_encoder {
+        import_lines.push(String::from("import json"));
+    }
+    if has_uuid_property {
+        import_lines.push(String::from("import uuid"));
+    }
+    let mut typing_extensions_imports = vec![];
+    if uses_style(OutputStyle::Dataclass) {
+        import_lines.push(String::from("from dataclasses import dataclass"));
+    }
+    if uses_style(OutputStyle::Msgspec) {
+        import_lines.push(String::from("import msgspec"));
+    }
+    if uses_style(OutputStyle::Polars) {
+        import_lines.push(String::from("import polars as pl"));
+    }
+    if uses_style(OutputStyle::Pydantic) {
+        let mut properties = dicts
+            .iter()
+            .filter(|dict| {
+                !options.is_flattened(dict)
+                    && options.output_style_for(dict) == OutputStyle::Pydantic
+            })
+            .flat_map(|dict| &dict.properties);
+        let has_json_property = properties
+            .clone()
+            .any(|property| property.data_type == PythonDataType::Json);
+        let has_length_constrained_property = properties.clone().any(|property| {
+            property.data_type == PythonDataType::String && property.max_length.is_some()
+        });
+        let has_bounded_property = properties
+            .clone()
+            .any(|property| property.has_check_bounds());
+        let has_commented_property = properties.any(|property| property.comment.is_some());
+
+        let mut pydantic_imports = vec!["BaseModel"];
+        if has_commented_property || has_bounded_property {
+            // comments are rendered as `Field(description=...)` and bounds as `Field(ge=...)`
+            pydantic_imports.push("Field");
+        }
+        if has_length_constrained_property {
+            pydantic_imports.push("StringConstraints");
+        }
+        if has_length_constrained_property || has_bounded_property {
+            if minimum_python_version >= MinimumPythonVersion::Python3_10 {
+                typing_imports.push("Annotated");
+            } else {
+                typing_extensions_imports.push("Annotated"); // no `typing.Annotated` before Python 3.9
+            }
+        }
+        match options.pydantic_json {
+            Some(PydanticJson::Json) if has_json_property => pydantic_imports.push("Json"),
+            Some(PydanticJson::Dict)
+                if has_json_property
+                    && minimum_python_version < MinimumPythonVersion::Python3_10 =>
+            {
+                typing_imports.push("Dict")
+            }
+            _ => {}
+        }
+        import_lines.push(format!(
+            "from pydantic import {}",
+            pydantic_imports.join(", ")
+        ));
+    }
+    if uses_style(OutputStyle::SqlAlchemy) {
+        let column_types = dicts
+            .iter()
+            .filter(|dict| {
+                !options.is_flattened(dict)
+                    && options.output_style_for(dict) == OutputStyle::SqlAlchemy
+            })
+            .flat_map(|dict| &dict.properties)
+            .filter(|property| !property.data_type.is_sqlalchemy_annotation_mapped())
+            .map(|property| property.data_type.as_sqlalchemy_type_str())
+            .collect::<Vec<String>>();
+        // e.g. both `ARRAY` and `Integer` for `ARRAY(Integer)`
+        let column_type_names = column_types
+            .iter()
+            .flat_map(|column_type| column_type.split(['(', ')']))
+            .filter(|name| !name.is_empty())
+            .sorted()
+            .dedup()
+            .join(", ");
+        if !column_type_names.is_empty() {
+            import_lines.push(format!("from sqlalchemy import {column_type_names}"));
+        }
+        import_lines.push(String::from(
+            "from sqlalchemy.orm import DeclarativeBase, Mapped, mapped_column",
+        ));
+    }
+    if options.emit_tablename
+        && (uses_style(OutputStyle::Dataclass)
+            || uses_style(OutputStyle::Pydantic)
+            || uses_style(OutputStyle::Msgspec))
+    {
+        typing_imports.push("ClassVar");
+    }
+    let uses_kwargs_variant = canonical_dicts.iter().any(|dict| has_kwargs_variant(dict));
+    if uses_style(OutputStyle::TypedDict) || uses_kwargs_variant {
+        typing_imports.push("TypedDict");
+    }
+    // `Unpack` and `NotRequired` were added in Python 3.11
+    let python_3_11_imports = if minimum_python_version >= MinimumPythonVersion::Python3_12 {
+        &mut typing_imports
+    } else {
+        &mut typing_extensions_imports
+    };
+    if uses_kwargs_variant || (uses_style(OutputStyle::TypedDict) && options.totality.marks_keys())
+    {
+        python_3_11_imports.push("NotRequired");
+    }
+    if uses_kwargs_variant {
+        python_3_11_imports.push("Unpack");
+    }
+    // each `NewType` is declared once, ahead of the classes that reference it, even if several variants share it
+    let new_types = canonical_dicts
+        .iter()
+        .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+        .flat_map(|dict| &dict.properties)
+        .filter_map(|property| match &property.data_type {
+            PythonDataType::NewType { name, base } => Some(format!(
+                "{} = NewType({}, {})\n",
+                name,
+                as_python_string_literal(name),
+                base.as_primitive_type_str()
+            )),
+            _ => None,
+        })
+        .unique()
+        .collect::<Vec<String>>();
+    if !new_types.is_empty() {
+        typing_imports.push("NewType");
+    }
+    // declared once, like the `NewType`s, for every class that has a timezone-aware datetime
+    let aware_datetime_alias = canonical_dicts
+        .iter()
+        .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+        .flat_map(|dict| &dict.properties)
+        .any(|property| property.data_type.uses(&PythonDataType::AwareDateTime))
+        .then(|| {
+            as_type_alias_str(
+                AWARE_DATETIME_ALIAS,
+                r#"Annotated[datetime.datetime, "UTC"]"#,
+                minimum_python_version,
+            )
+        });
+    // no `typing.Annotated` before Python 3.9
+    let annotated_imports = if minimum_python_version >= MinimumPythonVersion::Python3_10 {
+        &mut typing_imports
+    } else {
+        &mut typing_extensions_imports
+    };
+    if aware_datetime_alias.is_some() && !annotated_imports.contains(&"Annotated") {
+        annotated_imports.push("Annotated");
+    }
+    if registry.is_some() && minimum_python_version < MinimumPythonVersion::Python3_10 {
+        typing_imports.push("Dict"); // `dict` isn't subscriptable before Python 3.9
+    }
+
+    let has_type_alias = !aliases.is_empty()
+        || aware_datetime_alias.is_some()
+        || !discriminated_unions.is_empty()
+        || canonical_dicts
+            .iter()
+            .any(|dict| options.is_flattened(dict));
+    if !discriminated_unions.is_empty() && minimum_python_version < MinimumPythonVersion::Python3_10
+    {
+        typing_imports.push("Union"); // no `X | Y` unions before Python 3.10
+    }
+    if has_type_alias && minimum_python_version == MinimumPythonVersion::Python3_10 {
+        typing_imports.push("TypeAlias");
+    }
+    if !typing_imports.is_empty() {
+        import_lines.push(options.typing_import_style.as_import_line(&typing_imports));
+    }
+    if !typing_extensions_imports.is_empty() {
+        import_lines.push(format!(
+            "from typing_extensions import {}",
+            typing_extensions_imports.join(", ")
+        ));
+    }
+
+    let import_lines = if options.canonical {
+        as_canonical_import_lines(import_lines)
+    } else {
+        import_lines
+    };
+
+    let imports_start = result.len();
+    // the host file provides the imports when the definitions are embedded into a larger template
+    if !options.no_imports && !import_lines.is_empty() {
+        // a single line of imports can be pasted into a notebook cell without reordering anything
+        let import_lines = if options.single_cell {
+            import_lines
+                .into_iter()
+                .filter(|line| !line.is_empty())
+                .join("; ")
+        } else {
+            import_lines.join("\n")
+        };
+        result.push_str(import_lines.as_str());
+        result.push_str("\n\n\n");
+    }
+    let definitions_start = result.len();
+
+    let python_dicts_str = canonical_dicts
+        .iter()
+        .map(|dict| {
+            if options.is_flattened(dict) {
+                return as_type_alias_str(
+                    &dict.name,
+                    &dict.properties[0].as_property_type_str(minimum_python_version),
+                    minimum_python_version,
+                );
+            }
+
+            let class_str = match options.output_style_for(dict) {
+                OutputStyle::Dataclass => {
+                    dict.as_dataclass_str(minimum_python_version, options.dataclass_options)
+                }
+                OutputStyle::Pydantic => {
+                    dict.as_pydantic_model_str(minimum_python_version, options.pydantic_json)
+                }
+                OutputStyle::Msgspec => dict.as_msgspec_struct_str(minimum_python_version),
+                OutputStyle::SqlAlchemy => dict.as_sqlalchemy_model_str(minimum_python_version),
+                OutputStyle::TypedDict => {
+                    return as_typed_dict_str(dict, options);
+                }
+                OutputStyle::Polars => return dict.as_polars_schema_str(),
+            };
+
+            // `TypedDict` classes can't have methods or class variables, so only the other styles get these,
+            // except for SQLAlchemy models, which always have a `__tablename__`
+            let class_str = if options.emit_tablename
+                && options.output_style_for(dict) != OutputStyle::SqlAlchemy
+            {
+                insert_class_attribute(
+                    &class_str,
+                    &format!(
+                        "    __tablename__: ClassVar[str] = {}",
+                        as_python_string_literal(&dict.raw_name)
+                    ),
+                )
+            } else {
+                class_str
+            };
+
+            if options.emit_repr {
+                format!("{}{}", class_str, dict.as_repr_method_str())
+            } else {
+                class_str
+            }
+        })
+        .zip(&canonical_dicts)
+        .map(|(class_str, dict)| {
+            // `TypedDict`s can't have methods, and the other styles already have a constructor
+            if options.emit_factory_functions
+                && options.output_style_for(dict) == OutputStyle::TypedDict
+                && !options.is_flattened(dict)
+                && !requires_backward_compat(dict)
+            {
+                format!(
+                    "{}\n\n{}",
+                    class_str,
+                    as_factory_function_str(dict, minimum_python_version)
+                )
+            } else {
+                class_str
+            }
+        })
+        .zip(&canonical_dicts)
+        .map(|(class_str, dict)| {
+            if has_kwargs_variant(dict) {
+                format!(
+                    "{}\n\n{}",
+                    class_str,
+                    as_kwargs_variant_str(dict, minimum_python_version)
+                )
+            } else {
+                class_str
+            }
+        })
+        .zip(&canonical_dicts)
+        .map(|(class_str, dict)| {
+            if options.column_count_comment {
+                format!("{}\n{}", as_column_count_comment(dict), class_str)
+            } else {
+                class_str
+            }
+        })
+        .zip(&canonical_dicts)
+        .map(|(class_str, dict)| {
+            if options.keys_comment {
+                format!("{}\n{}", as_keys_comment(dict), class_str)
+            } else {
+                class_str
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    if let Some(aware_datetime_alias) = &aware_datetime_alias {
+        result.push_str(aware_datetime_alias);
+        result.push_str("\n\n");
+    }
+    if !new_types.is_empty() {
+        result.push_str(new_types.concat().as_str());
+        result.push_str("\n\n");
+    }
+    // the models share one `Base`, and so one `MetaData`
+    if uses_style(OutputStyle::SqlAlchemy) {
+        result.push_str("class Base(DeclarativeBase):\n    pass\n\n\n");
+    }
+    result.push_str(python_dicts_str.as_str());
+
+    if !aliases.is_empty() {
+        result.push_str("\n\n");
+        for (alias, canonical) in aliases {
+            result.push_str(&as_type_alias_str(alias, canonical, minimum_python_version));
+        }
+    }
+
+    for union in &discriminated_unions {
+        let variants = if minimum_python_version >= MinimumPythonVersion::Python3_10 {
+            union.variants.join(" | ")
+        } else {
+            format!("Union[{}]", union.variants.join(", "))
+        };
+        result.push_str(&format!(
+            "\n\n# discriminated by `{}`, the variants don't differ in their other columns yet\n",
+            union.discriminator_column
+        ));
+        result.push_str(&as_type_alias_str(
+            &union.name,
+            &variants,
+            minimum_python_version,
+        ));
+    }
+
+    if let Some(registry) = &registry {
+        result.push_str("\n\n");
+        result.push_str(registry);
+    }
+
+    if options.emit_json_encoder {
+        result.push_str("\n\n");
+        result.push_str(&as_json_encoder_str(
+            dicts
+                .iter()
+                .filter(|dict| options.output_style_for(dict) != OutputStyle::Polars)
+                .flat_map(|dict| &dict.properties),
+        ));
+    }
+
+    if options.typing_import_style != TypingImportStyle::From {
+        let definitions = result.split_off(definitions_start);
+        result.push_str(&with_typing_names_renamed(
+            &definitions,
+            &typing_imports,
+            options.typing_import_style,
+        ));
+    }
+
+    // only `TYPE_CHECKING` itself is imported at runtime, everything else exists for type checkers only
+    if options.types_only_module {
+        let guarded = result.split_off(imports_start);
+        result.push_str("from typing import TYPE_CHECKING\n\nif TYPE_CHECKING:\n");
+        for line in guarded.lines() {
+            if !line.is_empty() {
+                result.push_str("    ");
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    if options.keep_trailing_whitespace {
+        result
+    } else {
+        with_normalized_whitespace(&result)
+    }
+}
+
+/// Strips the trailing whitespace of every line and ends the source with exactly one newline,
+/// as expected by pre-commit hooks like `trailing-whitespace` and `end-of-file-fixer`
+fn with_normalized_whitespace(source: &str) -> String {
+    let lines = source.lines().map(str::trim_end).join("\n");
+
+    format!("{}\n", lines.trim_end())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db_introspector::CheckBound;
+
+    fn column(
+        table_name: &str,
+        column_name: &str,
+        nullable: bool,
+        data_type: &str,
+    ) -> TableColumnDefinition {
+        TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable,
+            data_type: String::from(data_type),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        }
+    }
+
+    /// Converts `table_column_definitions` and writes them to a single string
+    fn render(
+        table_column_definitions: Vec<TableColumnDefinition>,
+        conversion_options: &ConversionOptions,
+        writer_options: &WriterOptions,
+    ) -> String {
+        write_python_dicts_to_str(
+            convert_table_column_definitions_to_python_dicts(
+                table_column_definitions,
+                conversion_options,
+            ),
+            writer_options,
+        )
+    }
+
+    #[test]
+    fn convert_definitions_to_single_dict_for_single_table() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("column_one"),
+                nullable: false,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("column_two"),
+                nullable: true,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+
+        let expected = vec![PythonTypedDict {
+            name: String::from("SomeTable"),
+            raw_name: String::from("some_table"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("column_two"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn convert_definitions_to_two_dicts_for_two_tables() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("some_other_table"),
+                column_name: String::from("column_one"),
+                nullable: true,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("column_one"),
+                nullable: false,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+
+        let expected = vec![
+            PythonTypedDict {
+                name: String::from("SomeOtherTable"),
+                raw_name: String::from("some_other_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("SomeTable"),
+                raw_name: String::from("some_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn merges_tables_differing_only_in_case_into_one_class() {
+        let column = |table_name: &str, column_name: &str| TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable: false,
+            data_type: String::from("int"),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        };
+        // as reported by MySQL with `lower_case_table_names=2`
+        let table_column_definitions = vec![
+            column("users", "id"),
+            column("users", "org_id"),
+            column("Users", "id"),
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "Users");
+        assert_eq!(result[0].raw_name, "Users");
+        assert_eq!(
+            result[0]
+                .properties
+                .iter()
+                .map(|property| property.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["id", "org_id"]
+        );
+    }
+
+    #[test]
+    fn sorts_python_dict_vec_correctly() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("b_table"),
+                column_name: String::from("column_one"),
+                nullable: false,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("a_table"),
+                column_name: String::from("column_one"),
+                nullable: true,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+
+        let expected = vec![
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn includes_only_string_columns() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("created_at"),
+                nullable: false,
+                data_type: String::from("timestamp"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("description"),
+                nullable: true,
+                data_type: String::from("text"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("id"),
+                nullable: false,
+                data_type: String::from("int"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("some_table"),
+                column_name: String::from("title"),
+                nullable: false,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("numbers_only"),
+                column_name: String::from("id"),
+                nullable: false,
+                data_type: String::from("int"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions {
+                include_column_types: vec![PythonDataType::String],
+                ..Default::default()
+            },
+        );
+
+        // `numbers_only` is left without any columns, so it is skipped entirely
+        let expected = vec![PythonTypedDict {
+            name: String::from("SomeTable"),
+            raw_name: String::from("some_table"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("description"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("title"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn decorates_class_names_with_prefix_and_suffix() {
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("order_items", "id", false, "integer"),
+                column("user", "id", false, "integer"),
+            ],
+            &ConversionOptions {
+                class_name_prefix: String::from("Db"),
+                class_name_suffix: String::from("Row"),
+                ..Default::default()
+            },
+        );
+
+        let names = dicts
+            .iter()
+            .map(|dict| (dict.name.as_str(), dict.raw_name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![("DbOrderItemsRow", "order_items"), ("DbUserRow", "user")]
+        );
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+        assert!(result.contains("class DbUserRow(TypedDict):\n"));
+    }
+
+    #[test]
+    fn prefixes_same_named_tables_of_several_schemas_with_their_schema() {
+        let column = |schema: &str, table_name: &str, column_name: &str| TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable: false,
+            data_type: String::from("integer"),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: Some(String::from(schema)),
+        };
+
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("auth", "users", "id"),
+                column("auth", "users", "password_hash"),
+                column("public", "users", "id"),
+                column("public", "orders", "id"),
+            ],
+            &ConversionOptions::default(),
+        );
+
+        let names = dicts
+            .iter()
+            .map(|dict| {
+                (
+                    dict.name.as_str(),
+                    dict.raw_name.as_str(),
+                    dict.properties.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("AuthUsers", "users", 2),
+                ("Orders", "orders", 1),
+                ("PublicUsers", "users", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_both_definitions_of_a_duplicate_column() {
+        let column = |table_name: &str, data_type: &str, nullable: bool| TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from("email"),
+            nullable,
+            data_type: String::from(data_type),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        };
+
+        assert_eq!(
+            duplicate_column_error(
+                &[column("users", "varchar", false), column("Users", "text", true)],
+                &ConversionOptions::default(),
+            ),
+            Some(String::from(
+                "Duplicate column `Users.email`: `users.email` (varchar, not null) conflicts with `Users.email` (text, nullable)"
+            ))
+        );
+        assert_eq!(
+            duplicate_column_error(
+                &[
+                    column("users", "varchar", false),
+                    column("orders", "text", true)
+                ],
+                &ConversionOptions::default(),
+            ),
+            None
+        );
+
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("users", "varchar", false),
+                column("users", "text", true),
+            ],
+            &ConversionOptions::default(),
+        );
+        assert_eq!(dicts[0].properties.len(), 1);
+        assert!(!dicts[0].properties[0].nullable);
+    }
+
+    #[test]
+    fn detects_identifiers_colliding_after_nfkc_normalization() {
+        let column = |table_name: &str, column_name: &str| TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable: false,
+            data_type: String::from("text"),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        };
+        // a precomposed `é` and an `e` with a combining accent, and the `ﬁ` ligature and `fi`
+        let columns = || {
+            vec![
+                column("caf\u{e9}", "\u{fb01}le"),
+                column("caf\u{e9}", "file"),
+                column("cafe\u{301}", "id"),
+            ]
+        };
+        let properties = |normalize_identifiers: bool| {
+            convert_table_column_definitions_to_python_dicts(
+                columns(),
+                &ConversionOptions {
+                    normalize_identifiers,
+                    ..Default::default()
+                },
+            )
+            .into_iter()
+            .map(|dict| {
+                (
+                    dict.name,
+                    dict.properties.into_iter().map(|p| p.name).collect(),
+                )
+            })
+            .collect::<Vec<(String, Vec<String>)>>()
+        };
+
+        assert_eq!(
+            properties(true),
+            vec![(
+                String::from("Caf\u{e9}"),
+                vec![String::from("id"), String::from("file")]
+            )]
+        );
+        assert_eq!(properties(false).len(), 2);
+    }
+
+    #[test]
+    fn warns_about_tables_emptied_by_column_filters() {
+        let table_definitions = || {
+            vec![
+                column("counters", "hits", false, "bigint"),
+                column("counters", "id", false, "integer"),
+                column("tags", "name", false, "text"),
+                column("users", "id", false, "integer"),
+                column("users", "name", false, "text"),
+            ]
+        };
+
+        let options = ConversionOptions {
+            exclude_column_types: vec![PythonDataType::Integer],
+            ..Default::default()
+        };
+        assert_eq!(
+            emptied_table_warnings(&table_definitions(), &options),
+            vec!["skipping table `counters` because --exclude-column-types leaves it without any columns"]
+        );
+        let names = convert_table_column_definitions_to_python_dicts(table_definitions(), &options)
+            .into_iter()
+            .map(|dict| dict.name)
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["Tags", "Users"]);
+
+        let options = ConversionOptions {
+            include_column_types: vec![PythonDataType::String],
+            ..Default::default()
+        };
+        assert_eq!(
+            emptied_table_warnings(&table_definitions(), &options),
+            vec!["skipping table `counters` because --include-column-types leaves it without any columns"]
+        );
+    }
+
+    #[test]
+    fn warns_about_types_mapped_to_any() {
+        let table_definitions = vec![
+            column("pages", "ancestors", true, "ltree[]"),
+            column("pages", "attrs", true, "hstore"),
+            column("pages", "id", false, "integer"),
+            column("pages", "path", true, "ltree"),
+            column("pages", "slug", true, "citext"),
+            column("users", "email", false, "citext"),
+        ];
+
+        assert_eq!(
+            unmapped_type_warning(&table_definitions, &ConversionOptions::default()).as_deref(),
+            Some("3 column types mapped to Any: citext, hstore, ltree")
+        );
+
+        let options = ConversionOptions {
+            exclude_column_types: vec![PythonDataType::Any],
+            ..Default::default()
+        };
+        // `ancestors` is a list, which excluding `any` doesn't drop
+        assert_eq!(
+            unmapped_type_warning(&table_definitions, &options).as_deref(),
+            Some("1 column type mapped to Any: ltree")
+        );
+        assert_eq!(
+            unmapped_column_error(&table_definitions, &options).as_deref(),
+            Some("Columns typed as Any because their types have no Python mapping: `pages.ancestors` (ltree[])")
+        );
+
+        let table_definitions = vec![column("users", "id", false, "integer")];
+        assert_eq!(
+            unmapped_column_error(&table_definitions, &ConversionOptions::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn assumes_matching_columns_are_not_null() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("orders"),
+                column_name: String::from("customer_id"),
+                nullable: true,
+                data_type: String::from("int"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("orders"),
+                column_name: String::from("id"),
+                nullable: true,
+                data_type: String::from("int"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("orders"),
+                column_name: String::from("notes"),
+                nullable: true,
+                data_type: String::from("text"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions {
+                assume_not_null: vec![Pattern::new("*_id").unwrap()],
+                ..Default::default()
+            },
+        );
+
+        let expected = vec![PythonTypedDict {
+            name: String::from("Orders"),
+            raw_name: String::from("orders"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("customer_id"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("id"),
+                    nullable: true,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("notes"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn truncates_long_comments() {
+        let column = |column_name: &str, comment: &str| TableColumnDefinition {
+            table_name: String::from("users"),
+            column_name: String::from(column_name),
+            nullable: false,
+            data_type: String::from("text"),
+            comment: Some(String::from(comment)),
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        };
+
+        let result = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column(
+                    "bio",
+                    "A paragraph about the user, shown on their profile page",
+                ),
+                column("name", "Display name"),
+            ],
+            &ConversionOptions {
+                comment_max_length: NonZeroUsize::new(20),
+                ..Default::default()
+            },
+        );
+
+        let comments = result[0]
+            .properties
+            .iter()
+            .map(|property| property.comment.as_deref().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(comments, vec!["A paragraph about t…", "Display name"]);
+        assert_eq!(comments[0].chars().count(), 20);
+    }
+
+    fn generate_preamble(
+        minimum_python_version: MinimumPythonVersion,
+        import_lines: &str,
+    ) -> String {
+        let version = match minimum_python_version {
+            MinimumPythonVersion::Python3_6 => "3.6",
+            MinimumPythonVersion::Python3_8 => "3.8",
+            MinimumPythonVersion::Python3_10 => "3.10",
+            MinimumPythonVersion::Python3_12 => "3.12",
+        };
+        formatdoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= {version}
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            {import_lines}
+        "}
+    }
+
+    #[test]
+    fn writes_single_dict_to_string() {
+        let dict = vec![PythonTypedDict {
+            name: String::from("SomeTable"),
+            raw_name: String::from("some_table"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("column_two"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(dict, &WriterOptions::default());
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class SomeTable(TypedDict):
+                column_one: str
+                column_two: str | None
+            "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_single_dict_to_string_python_3_12() {
+        let dict = vec![PythonTypedDict {
+            name: String::from("SomeTable"),
+            raw_name: String::from("some_table"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("column_two"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dict,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_12,
+                ..Default::default()
+            },
+        );
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_12,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class SomeTable(TypedDict):
+                column_one: str
+                column_two: str | None
+            "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_multiple_dict_to_string() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class ATable(TypedDict):
+                column_one: str | None
+
+
+            class BTable(TypedDict):
+                column_one: str
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn ignores_writing_dict_with_dollar_sign_in_name() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("ATable$"),
+                raw_name: String::from("a_table$"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class BTable(TypedDict):
+                column_one: str
+            "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn ignores_writing_dict_name_starts_with_number() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("1Table"),
+                raw_name: String::from("1_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class BTable(TypedDict):
+                column_one: str
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn first_dict_backwards_compat() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("1column"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+
+        // remember that in format strings {{ and }} are escaped `{` and `}` respectively
+        let expected = formatdoc! {"
+            {preamble}
+
+            ATable = TypedDict('ATable', {{
+                '1column': str | None
+            }})
+
+
+            class BTable(TypedDict):
+                column_one: str
+            "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn supports_python_3_6() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("1column"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_6,
+                ..Default::default()
+            },
+        );
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_6,
+            "from typing import Optional, TypedDict",
+        );
+
+        // remember that in format strings {{ and }} are escaped `{` and `}` respectively
+        let expected = formatdoc! {"
+            {preamble}
+
+            ATable = TypedDict('ATable', {{
+                '1column': Optional[str]
+            }})
+
+
+            BTable = TypedDict('BTable', {{
+                'column_one': str
+            }})
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_array_columns_as_lists() {
+        let result = render(
+            vec![
+                column("grids", "cells", true, "integer[][]"),
+                column("grids", "flags", false, "boolean[]"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("from typing import Optional, List, TypedDict\n"));
+        assert!(result.contains("    cells: Optional[List[List[int]]]\n"));
+        assert!(result.contains("    flags: List[bool]\n"));
+    }
+
+    #[test]
+    fn types_exact_numerics_as_decimals_when_asked() {
+        let write = |decimal_as_decimal: bool| {
+            render(
+                vec![
+                    column("orders", "total", false, "numeric"),
+                    column("orders", "weight", true, "double precision"),
+                ],
+                &ConversionOptions {
+                    decimal_as_decimal,
+                    ..Default::default()
+                },
+                &WriterOptions::default(),
+            )
+        };
+
+        let result = write(true);
+        assert!(result.contains("import decimal\nfrom typing import TypedDict\n"));
+        assert!(result.contains("    total: decimal.Decimal\n"));
+        assert!(result.contains("    weight: float | None\n"));
+
+        let result = write(false);
+        assert!(!result.contains("import decimal"));
+        assert!(result.contains("    total: float\n"));
+    }
+
+    #[test]
+    fn writes_time_columns_with_the_datetime_import() {
+        let result = render(
+            vec![column("shifts", "starts_at", false, "time")],
+            &ConversionOptions::default(),
+            &WriterOptions::default(),
+        );
+
+        assert!(result.contains("import datetime\n"));
+        assert!(result.contains("    starts_at: datetime.time\n"));
+    }
+
+    #[test]
+    fn writes_interval_columns_as_timedeltas() {
+        let result = render(
+            vec![column("jobs", "timeout", false, "interval")],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                emit_json_encoder: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("import datetime\n"));
+        assert!(result.contains("    timeout: datetime.timedelta\n"));
+        assert!(result.contains(
+            "        if isinstance(o, datetime.timedelta):\n            return o.total_seconds()\n"
+        ));
+    }
+
+    #[test]
+    fn aliases_identical_dicts() {
+        let properties = vec![
+            PythonDictProperty {
+                name: String::from("id"),
+                nullable: false,
+                data_type: PythonDataType::Integer,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            },
+            PythonDictProperty {
+                name: String::from("payload"),
+                nullable: true,
+                data_type: PythonDataType::String,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            },
+        ];
+
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Events2024"),
+                raw_name: String::from("events_2024"),
+                properties: properties.clone(),
+            },
+            PythonTypedDict {
+                name: String::from("Events2023"),
+                raw_name: String::from("events_2023"),
+                properties: properties.clone(),
+            },
+            PythonTypedDict {
+                name: String::from("Events2025"),
+                raw_name: String::from("events_2025"),
+                properties,
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                alias_identical: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import TypedDict, TypeAlias
+
+
+            class Events2023(TypedDict):
+                id: int
+                payload: str | None
+
+
+            Events2024: TypeAlias = Events2023
+            Events2025: TypeAlias = Events2023
+        "};
+
+        assert_eq!(result, expected);
+
+        // `TypeAlias` was only added in Python 3.10
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                alias_identical: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("from typing import Optional, TypedDict\n"));
+        assert!(result.ends_with("Events2024 = Events2023\nEvents2025 = Events2023\n"));
+    }
+
+    #[test]
+    fn writes_kwargs_variant_with_unpack_stub() {
+        let result = render(
+            vec![
+                column("users", "email", true, "text"),
+                column("users", "id", false, "integer"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_12,
+                kwargs_variant: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.12
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import TypedDict, NotRequired, Unpack
+
+
+            class Users(TypedDict):
+                email: str | None
+                id: int
+
+
+            class UsersKwargs(TypedDict):
+                email: NotRequired[str | None]
+                id: int
+
+
+            def create_users(**kwargs: Unpack[UsersKwargs]) -> None: ...
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_typed_dict_factory_functions() {
+        let result = render(
+            vec![
+                column("user_accounts", "bio", true, "text"),
+                column("user_accounts", "id", false, "integer"),
+                column("user_accounts", "name", false, "text"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                emit_factory_functions: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains(indoc! {"
+            class UserAccounts(TypedDict):
+                bio: str | None
+                id: int
+                name: str
+
+
+            def make_user_accounts(id: int, name: str, bio: str | None = None) -> UserAccounts:
+                return UserAccounts(bio=bio, id=id, name=name)
+        "}));
+    }
+
+    #[test]
+    fn writes_tinyint_as_int_literal() {
+        let result = render(
+            vec![TableColumnDefinition {
+                column_type: Some(String::from("tinyint(1)")),
+                ..column("orders", "is_paid", false, "tinyint")
+            }],
+            &ConversionOptions {
+                bool_as_int_literal: true,
+                ..Default::default()
+            },
+            &WriterOptions::default(),
+        );
+
+        assert!(result.contains("from typing import Literal, TypedDict\n"));
+        assert!(result.contains("    is_paid: Literal[0, 1]\n"));
+    }
+
+    #[test]
+    fn writes_wider_tinyints_as_int() {
+        let result = render(
+            vec![
+                TableColumnDefinition {
+                    column_type: Some(String::from("tinyint(1)")),
+                    ..column("orders", "is_paid", false, "tinyint")
+                },
+                TableColumnDefinition {
+                    column_type: Some(String::from("tinyint(4)")),
+                    ..column("orders", "quantity", false, "tinyint")
+                },
+            ],
+            &ConversionOptions {
+                bool_as_int_literal: true,
+                ..Default::default()
+            },
+            &WriterOptions::default(),
+        );
+
+        assert!(result.contains("    is_paid: Literal[0, 1]\n"));
+        assert!(result.contains("    quantity: int\n"));
+    }
+
+    #[test]
+    fn groups_columns_by_prefix_into_nested_dicts() {
+        let result = render(
+            vec![
+                column("users", "address_city", false, "text"),
+                column("users", "address_street", false, "text"),
+                column("users", "address_zip", true, "text"),
+                column("users", "created_at", false, "timestamp"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("users", "id", false, "integer")
+                },
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                group_columns_by_prefix: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            import datetime
+            from typing import TypedDict
+
+
+            class UsersAddress(TypedDict):
+                city: str
+                street: str
+                zip: str | None
+
+
+            class Users(TypedDict):
+                address: UsersAddress
+                created_at: datetime.datetime
+                id: int
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_new_types_for_primary_keys() {
+        let result = render(
+            vec![
+                column("users", "email", false, "text"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("users", "id", false, "integer")
+                },
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                pk_newtypes: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import TypedDict, NewType
+
+
+            UsersId = NewType("UsersId", int)
+
+
+            class Users(TypedDict):
+                email: str
+                id: UsersId
+        "#};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn flattens_single_column_tables_into_type_aliases() {
+        let property = |name: &str, nullable: bool| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type: PythonDataType::String,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("StatusCode"),
+                raw_name: String::from("status_code"),
+                properties: vec![property("code", false)],
+            },
+            PythonTypedDict {
+                name: String::from("Users"),
+                raw_name: String::from("users"),
+                properties: vec![property("email", true), property("name", false)],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                flatten_single_column: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import TypedDict, TypeAlias
+
+
+            StatusCode: TypeAlias = str
+
+
+            class Users(TypedDict):
+                email: str | None
+                name: str
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_type_statement_aliases_for_python_3_12() {
+        let properties = vec![PythonDictProperty {
+            name: String::from("id"),
+            nullable: false,
+            data_type: PythonDataType::Integer,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        }];
+
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Events2023"),
+                raw_name: String::from("events_2023"),
+                properties: properties.clone(),
+            },
+            PythonTypedDict {
+                name: String::from("Events2024"),
+                raw_name: String::from("events_2024"),
+                properties,
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_12,
+                alias_identical: true,
+                ..Default::default()
+            },
+        );
+
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_12,
+            "from typing import TypedDict",
+        );
+        let expected = formatdoc! {"
+            {preamble}
+
+            class Events2023(TypedDict):
+                id: int
+
+
+            type Events2024 = Events2023
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_inferred_enums_as_literals() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("orders"),
+                column_name: String::from("notes"),
+                nullable: true,
+                data_type: String::from("text"),
+                comment: None,
+                distinct_values: Some(
+                    (0..=MAX_INFERRED_ENUM_VALUES)
+                        .map(|i| i.to_string())
+                        .collect(),
+                ),
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("orders"),
+                column_name: String::from("status"),
+                nullable: false,
+                data_type: String::from("varchar"),
+                comment: None,
+                distinct_values: Some(vec![String::from("pending"), String::from("paid")]),
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import Literal, TypedDict
+
+
+            class Orders(TypedDict):
+                notes: str | None
+                status: Literal["paid", "pending"]
+        "#};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_mysql_enums_as_literals() {
+        let dicts = || {
+            convert_table_column_definitions_to_python_dicts(
+                vec![TableColumnDefinition {
+                    table_name: String::from("orders"),
+                    column_name: String::from("status"),
+                    nullable: true,
+                    data_type: String::from("enum"),
+                    comment: None,
+                    distinct_values: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                    column_type: Some(String::from("enum('pending','paid')")),
+                    schema: None,
+                }],
+                &ConversionOptions::default(),
+            )
+        };
+
+        let result = write_python_dicts_to_str(dicts(), &WriterOptions::default());
+        assert!(result.contains("from typing import Literal, TypedDict\n"));
+        assert!(result.contains("    status: Literal[\"pending\", \"paid\"] | None\n"));
+
+        let result = write_python_dicts_to_str(
+            dicts(),
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                ..Default::default()
+            },
+        );
+        assert!(result.contains("    status: Optional[Literal[\"pending\", \"paid\"]]\n"));
+    }
+
+    #[test]
+    fn writes_resolved_postgres_enums_as_literals() {
+        let column =
+            |column_name: &str, data_type: &str, column_type: Option<&str>| TableColumnDefinition {
+                table_name: String::from("people"),
+                column_name: String::from(column_name),
+                nullable: false,
+                data_type: String::from(data_type),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: column_type.map(String::from),
+                schema: None,
+            };
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("mood", "USER-DEFINED", Some("enum('happy','sad')")),
+                column("past_moods", "USER-DEFINED[]", Some("enum('happy','sad')")),
+                // an enum of a schema that isn't on the `search_path`
+                column("other_mood", "USER-DEFINED", None),
+            ],
+            &ConversionOptions::default(),
+        );
+
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+        assert!(result.contains("    mood: Literal[\"happy\", \"sad\"]\n"));
+        assert!(result.contains("    other_mood: str\n"));
+        assert!(result.contains("    past_moods: list[Literal[\"happy\", \"sad\"]]\n"));
+    }
+
+    #[test]
+    fn writes_discriminated_union_of_enum_variants() {
+        let property = |name: &str, data_type: PythonDataType| PythonDictProperty {
+            name: String::from(name),
+            nullable: false,
+            data_type,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![PythonTypedDict {
+            name: String::from("Events"),
+            raw_name: String::from("events"),
+            properties: vec![
+                property("id", PythonDataType::Integer),
+                property(
+                    "kind",
+                    PythonDataType::Literal(vec![
+                        String::from("\"click\""),
+                        String::from("\"page_view\""),
+                    ]),
+                ),
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                discriminator_column: Some(String::from("kind")),
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from typing import Literal, TypedDict, TypeAlias
+
+
+            class Events(TypedDict):
+                id: int
+                kind: Literal["click", "page_view"]
+
+
+            class EventsClick(TypedDict):
+                id: int
+                kind: Literal["click"]
+
+
+            class EventsPageView(TypedDict):
+                id: int
+                kind: Literal["page_view"]
+
+
+            # discriminated by `kind`, the variants don't differ in their other columns yet
+            EventsVariant: TypeAlias = EventsClick | EventsPageView
+        "#};
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn writes_style_overrides_per_table() {
+        let property = PythonDictProperty {
+            name: String::from("id"),
+            nullable: false,
+            data_type: PythonDataType::Integer,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("AuditLogs"),
+                raw_name: String::from("audit_logs"),
+                properties: vec![property.clone()],
+            },
+            PythonTypedDict {
+                name: String::from("Users"),
+                raw_name: String::from("users"),
+                properties: vec![property],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                alias_identical: true,
+                style_overrides: vec![(String::from("users"), OutputStyle::Pydantic)],
+                ..Default::default()
+            },
+        );
+
+        // identical tables in different styles aren't aliased to each other
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from pydantic import BaseModel
+            from typing import TypedDict
+
+
+            class AuditLogs(TypedDict):
+                id: int
+
+
+            class Users(BaseModel):
+                id: int
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_total_false_for_every_typed_dict() {
+        let property = |name: &str, nullable: bool| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type: PythonDataType::Integer,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Orders"),
+                raw_name: String::from("orders"),
+                properties: vec![property("id", false)],
+            },
+            PythonTypedDict {
+                name: String::from("Users"),
+                raw_name: String::from("users"),
+                properties: vec![property("from", true)],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                totality: Totality::Partial,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            class Orders(TypedDict, total=False):
+                id: int
+
+
+            Users = TypedDict('Users', {
+                'from': int | None
+            }, total=False)
+        "};
+
+        assert!(result.ends_with(expected));
+    }
+
+    #[test]
+    fn writes_partial_typed_dicts_with_not_required_keys() {
+        let property = |name: &str, nullable| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type: PythonDataType::Integer,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Orders"),
+                raw_name: String::from("orders"),
+                properties: vec![property("id", false)],
+            },
+            PythonTypedDict {
+                name: String::from("Users"),
+                raw_name: String::from("users"),
+                properties: vec![property("from", true)],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                totality: Totality::PartialPerKey,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            from typing import TypedDict
+            from typing_extensions import NotRequired
+
+
+            class Orders(TypedDict):
+                id: NotRequired[int]
+
+
+            Users = TypedDict('Users', {
+                'from': NotRequired[int | None]
+            })
+        "};
+
+        assert!(result.ends_with(expected));
+    }
+
+    #[test]
+    fn writes_nullable_columns_as_not_required_keys() {
+        let write = |minimum_python_version| {
+            render(
+                vec![
+                    column("users", "email", true, "text"),
+                    column("users", "id", false, "integer"),
+                ],
+                &ConversionOptions::default(),
+                &WriterOptions {
+                    minimum_python_version,
+                    totality: Totality::NullableNotRequired,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let result = write(MinimumPythonVersion::Python3_12);
+        assert!(result.contains("from typing import TypedDict, NotRequired\n"));
+        assert!(
+            result.contains("class Users(TypedDict):\n    email: NotRequired[str]\n    id: int\n")
+        );
+
+        let result = write(MinimumPythonVersion::Python3_10);
+        assert!(result.contains("from typing_extensions import NotRequired\n"));
+        assert!(result.contains("    email: NotRequired[str]\n"));
+    }
+
+    #[test]
+    fn names_functional_typed_dicts_after_their_class_or_table() {
+        let write = |typeddict_name_source: TypedDictNameSource, compact_max_columns| {
+            write_python_dicts_to_str(
+                vec![PythonTypedDict {
+                    name: String::from("UserAccounts"),
+                    raw_name: String::from("user_accounts"),
+                    properties: vec![PythonDictProperty {
+                        name: String::from("id"),
+                        nullable: false,
+                        data_type: PythonDataType::Integer,
+                        comment: None,
+                        max_length: None,
+                        check_bounds: vec![],
+                        primary_key: false,
+                    }],
+                }],
+                &WriterOptions {
+                    minimum_python_version: MinimumPythonVersion::Python3_6,
+                    compact_max_columns,
+                    typeddict_name_source,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(write(TypedDictNameSource::Class, None)
+            .contains("UserAccounts = TypedDict('UserAccounts', {\n"));
+        assert!(write(TypedDictNameSource::Table, None)
+            .contains("UserAccounts = TypedDict('user_accounts', {\n"));
+        assert!(write(TypedDictNameSource::Table, Some(1))
+            .contains("UserAccounts = TypedDict('user_accounts', {'id': int})\n"));
+    }
+
+    #[test]
+    fn writes_compact_typed_dicts_for_small_tables() {
+        let property = |name: &str, data_type: PythonDataType| PythonDictProperty {
+            name: String::from(name),
+            nullable: false,
+            data_type,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("Codes"),
+                raw_name: String::from("codes"),
+                properties: vec![
+                    property("id", PythonDataType::Integer),
+                    property("label", PythonDataType::String),
+                ],
+            },
+            PythonTypedDict {
+                name: String::from("Products"),
+                raw_name: String::from("products"),
+                properties: vec![
+                    property("id", PythonDataType::Integer),
+                    property("name", PythonDataType::String),
+                    property("price", PythonDataType::Float),
+                ],
+            },
+        ];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_6,
+                compact_max_columns: Some(2),
+                ..Default::default()
+            },
+        );
+
+        // `Products` has more columns than the threshold, so it keeps the multi-line syntax
+        let expected = indoc! {"
+            Codes = TypedDict('Codes', {'id': int, 'label': str})
+
+
+            Products = TypedDict('Products', {
+                'id': int,
+                'name': str,
+                'price': float
+            })
+        "};
+
+        assert!(result.ends_with(expected));
+    }
+
+    #[test]
+    fn writes_dataclasses_to_string() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("ATable"),
+            raw_name: String::from("a_table"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("column_one"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("column_two"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Dataclass,
+                dataclass_options: DataclassOptions {
+                    frozen: true,
+                    slots: false,
+                },
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from dataclasses import dataclass
+
+
+            @dataclass(frozen=True)
+            class ATable:
+                column_two: int
+                column_one: str | None = None
+        "};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_pydantic_json_for_jsonb_columns() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("events"),
+                column_name: String::from("id"),
+                nullable: false,
+                data_type: String::from("integer"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+            TableColumnDefinition {
+                table_name: String::from("events"),
+                column_name: String::from("payload"),
+                nullable: true,
+                data_type: String::from("jsonb"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            },
+        ];
+
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
+
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                pydantic_json: Some(PydanticJson::Json),
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from pydantic import BaseModel, Json
+            from typing import Any
+
+
+            class Events(BaseModel):
+                id: int
+                payload: Json[Any] | None = None
+        "};
+
+        assert_eq!(result, expected);
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                output_style: OutputStyle::Pydantic,
+                pydantic_json: Some(PydanticJson::Dict),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("from typing import Any, Optional, Dict\n"));
+        assert!(result.contains("    payload: Optional[Dict[str, Any]] = None\n"));
+    }
+
+    #[test]
+    fn writes_pydantic_field_descriptions_from_comments() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("Users"),
+            raw_name: String::from("users"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("email"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: Some(String::from("The user's \"primary\" email")),
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("id"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from pydantic import BaseModel, Field
+
+
+            class Users(BaseModel):
+                id: int
+                email: str | None = Field(default=None, description="The user's \"primary\" email")
+        "#};
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn writes_pydantic_string_constraints_for_max_length() {
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("users", "bio", true, "text"),
+                TableColumnDefinition {
+                    max_length: Some(255),
+                    ..column("users", "email", false, "varchar")
+                },
+                TableColumnDefinition {
+                    max_length: Some(50),
+                    ..column("users", "nickname", true, "varchar")
+                },
+            ],
+            &ConversionOptions::default(),
+        );
+
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from pydantic import BaseModel, StringConstraints
+            from typing import Annotated
+
+
+            class Users(BaseModel):
+                email: Annotated[str, StringConstraints(max_length=255)]
+                bio: str | None = None
+                nickname: Annotated[str, StringConstraints(max_length=50)] | None = None
+        "#};
+
+        assert_eq!(result, expected);
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                ..Default::default()
+            },
+        );
+
+        assert!(result
+            .contains("from typing import Optional\nfrom typing_extensions import Annotated\n"));
+        assert!(result.contains(
+            "nickname: Optional[Annotated[str, StringConstraints(max_length=50)]] = None\n"
+        ));
+    }
+
+    #[test]
+    fn writes_pydantic_field_bounds_for_check_constraints() {
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                TableColumnDefinition {
+                    check_bounds: vec![CheckBound {
+                        keyword: String::from("ge"),
+                        value: String::from("0"),
+                    }],
+                    ..column("people", "age", false, "int")
+                },
+                column("people", "name", true, "text"),
+                TableColumnDefinition {
+                    check_bounds: vec![
+                        CheckBound {
+                            keyword: String::from("ge"),
+                            value: String::from("0"),
+                        },
+                        CheckBound {
+                            keyword: String::from("le"),
+                            value: String::from("100"),
+                        },
+                    ],
+                    ..column("people", "score", true, "real")
+                },
+            ],
+            &ConversionOptions::default(),
+        );
+
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from pydantic import BaseModel, Field
+            from typing import Annotated
+
+
+            class People(BaseModel):
+                age: Annotated[int, Field(ge=0)]
+                name: str | None = None
+                score: Annotated[float, Field(ge=0, le=100)] | None = None
+        "#};
+
+        assert_eq!(result, expected);
+
+        // other styles have nowhere to validate the bounds
+        let result = write_python_dicts_to_str(dicts, &WriterOptions::default());
+
+        assert!(result.contains("    age: int\n"));
+    }
+
+    #[test]
+    fn writes_repr_for_first_few_fields() {
+        let property = |name: &str| PythonDictProperty {
+            name: String::from(name),
+            nullable: false,
+            data_type: PythonDataType::String,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dicts = vec![PythonTypedDict {
+            name: String::from("Users"),
+            raw_name: String::from("users"),
+            properties: vec![
+                property("id"),
+                property("email"),
+                property("name"),
+                property("bio"),
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Dataclass,
+                emit_repr: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            @dataclass
+            class Users:
+                id: str
+                email: str
+                name: str
+                bio: str
+
+                def __repr__(self) -> str:
+                    return f"Users(id={self.id!r}, email={self.email!r}, name={self.name!r})"
+        "#};
+
+        assert!(result.ends_with(expected));
+    }
+
+    #[test]
+    fn writes_repr_for_primary_key_fields() {
+        let property = |name: &str, primary_key: bool| PythonDictProperty {
+            name: String::from(name),
+            nullable: false,
+            data_type: PythonDataType::Integer,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key,
+        };
+        let dicts = vec![PythonTypedDict {
+            name: String::from("OrderItems"),
+            raw_name: String::from("order_items"),
+            properties: vec![
+                property("quantity", false),
+                property("order_id", true),
+                property("price", false),
+                property("product_id", true),
+            ],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Pydantic,
+                emit_repr: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.ends_with(
+            "        return f\"OrderItems(order_id={self.order_id!r}, product_id={self.product_id!r})\"\n"
+        ));
+    }
+
+    #[test]
+    fn writes_msgspec_structs_to_string() {
+        let result = render(
+            vec![
+                column("users", "created_at", false, "timestamp"),
+                column("users", "email", true, "text"),
+                column("users", "id", false, "integer"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                output_style: OutputStyle::Msgspec,
+                emit_tablename: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            import datetime
+            import msgspec
+            from typing import ClassVar
+
+
+            class Users(msgspec.Struct):
+                __tablename__: ClassVar[str] = "users"
+                created_at: datetime.datetime
+                id: int
+                email: str | None = None
+        "#};
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn writes_sqlalchemy_models_to_string() {
+        let result = render(
+            vec![
+                column("teams", "bio", true, "text"),
+                column("teams", "email", false, "text"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("teams", "id", false, "integer")
+                },
+                column("user_accounts", "bio", true, "text"),
+                column("user_accounts", "email", false, "text"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("user_accounts", "id", false, "integer")
+                },
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                output_style: OutputStyle::SqlAlchemy,
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                // identical tables are still mapped separately
+                alias_identical: true,
+                emit_tablename: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.8
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            from sqlalchemy.orm import DeclarativeBase, Mapped, mapped_column
+            from typing import Optional
+
+
+            class Base(DeclarativeBase):
+                pass
+
+
+            class Teams(Base):
+                __tablename__ = "teams"
+
+                bio: Mapped[Optional[str]] = mapped_column()
+                email: Mapped[str] = mapped_column()
+                id: Mapped[int] = mapped_column(primary_key=True)
+
+
+            class UserAccounts(Base):
+                __tablename__ = "user_accounts"
+
+                bio: Mapped[Optional[str]] = mapped_column()
+                email: Mapped[str] = mapped_column()
+                id: Mapped[int] = mapped_column(primary_key=True)
+        "#};
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn writes_sqlalchemy_column_types_the_annotation_doesnt_map() {
+        let result = render(
+            vec![
+                column("orders", "area", true, "geography"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("orders", "id", false, "int")
+                },
+                TableColumnDefinition {
+                    column_type: Some(String::from("tinyint(1)")),
+                    ..column("orders", "is_paid", false, "tinyint")
+                },
+                column("orders", "note", true, "text"),
+                column("orders", "tags", true, "text[]"),
+            ],
+            &ConversionOptions {
+                bool_as_int_literal: true,
+                ..Default::default()
+            },
+            &WriterOptions {
+                output_style: OutputStyle::SqlAlchemy,
+                pk_newtypes: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("from sqlalchemy import ARRAY, Integer, JSON, String\n"));
+        assert!(result.contains(indoc! {r#"
+            class Orders(Base):
+                __tablename__ = "orders"
+
+                area: Mapped[Any | None] = mapped_column(JSON)
+                id: Mapped[OrdersId] = mapped_column(Integer, primary_key=True)
+                is_paid: Mapped[Literal[0, 1]] = mapped_column(Integer)
+                note: Mapped[str | None] = mapped_column()
+                tags: Mapped[list[str] | None] = mapped_column(ARRAY(String))
+        "#}));
+    }
+
+    #[test]
+    fn skips_sqlalchemy_tables_without_primary_key() {
+        let result = render(
+            vec![
+                column("logs", "level", false, "int"),
+                column("logs", "message", true, "text"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("users", "id", false, "integer")
+                },
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                output_style: OutputStyle::SqlAlchemy,
+                emit_registry: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(!result.contains("Logs"));
+        assert!(result.contains("class Users(Base):\n"));
+        assert!(result.contains("MODELS: dict[str, type] = {\n    \"users\": Users,\n}\n"));
+    }
+
+    #[test]
+    fn counts_only_the_dicts_that_are_written() {
+        let dict = |name: &str, primary_key: bool| PythonTypedDict {
+            name: String::from(name),
+            raw_name: name.to_case(Case::Snake),
+            properties: vec![PythonDictProperty {
+                name: String::from("id"),
+                nullable: false,
+                data_type: PythonDataType::Integer,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key,
+            }],
+        };
+        let dicts = vec![
+            dict("Users", true),
+            dict("Logs", false),
+            dict("Pg$Stats", true),
+            dict("2fa", true),
+        ];
+
+        assert_eq!(count_written_dicts(&dicts, &WriterOptions::default()), 2);
+        let options = WriterOptions {
+            output_style: OutputStyle::SqlAlchemy,
+            ..Default::default()
+        };
+        assert_eq!(count_written_dicts(&dicts, &options), 1);
+    }
+
+    #[test]
+    fn writes_polars_schema_dicts() {
+        let result = render(
+            vec![
+                column("user_account", "active", false, "boolean"),
+                column("user_account", "avatar", true, "blob"),
+                column("user_account", "birthday", true, "date"),
+                column("user_account", "created_at", false, "timestamp"),
+                column("user_account", "id", false, "bigint"),
+                TableColumnDefinition {
+                    max_length: Some(255),
+                    ..column("user_account", "name", true, "varchar")
+                },
+                column("user_account", "score", true, "double precision"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                output_style: OutputStyle::Polars,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("import polars as pl\n"));
+        assert!(result.contains(indoc! {r#"
+            USER_ACCOUNT_SCHEMA = {
+                "active": pl.Boolean,
+                "avatar": pl.Binary,
+                "birthday": pl.Date,
+                "created_at": pl.Datetime,
+                "id": pl.Int64,
+                "name": pl.Utf8,
+                "score": pl.Float64,
+            }
+        "#}));
+        assert!(!result.contains("class "));
+    }
+
+    #[test]
+    fn writes_tablename_for_dataclasses() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("UserAccounts"),
+            raw_name: String::from("user_accounts"),
+            properties: vec![PythonDictProperty {
+                name: String::from("id"),
+                nullable: false,
+                data_type: PythonDataType::Integer,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                output_style: OutputStyle::Dataclass,
+                emit_tablename: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            from dataclasses import dataclass
+            from typing import ClassVar
+
+
+            @dataclass
+            class UserAccounts:
+                __tablename__: ClassVar[str] = "user_accounts"
+                id: int
+        "#};
+
+        assert!(result.ends_with(expected));
+    }
+
+    #[test]
+    fn omits_import_block_without_imports() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("Users"),
+            raw_name: String::from("users"),
+            properties: vec![PythonDictProperty {
+                name: String::from("created_at"),
+                nullable: false,
+                data_type: PythonDataType::DateTime,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                no_imports: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            class Users(TypedDict):
+                created_at: datetime.datetime
+        "};
+
+        assert_eq!(result, expected);
+        assert!(!result.contains("import"));
+    }
+
+    #[test]
+    fn imports_only_the_names_in_use() {
+        let result = render(
+            vec![
+                TableColumnDefinition {
+                    max_length: Some(255),
+                    ..column("users", "email", true, "varchar")
+                },
+                column("users", "name", false, "text"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions::default(),
+        );
+        assert!(result.contains("argument\n\nfrom typing import TypedDict\n\n\nclass Users"));
+
+        let result = render(
+            vec![
+                column("events", "at", false, "timestamp"),
+                column("events", "location", true, "geography"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions::default(),
+        );
+        assert!(result.contains("argument\n\nimport datetime\nfrom typing import Any, TypedDict\n"));
+    }
+
+    #[test]
+    fn imports_uuid_only_for_uuid_columns() {
+        let result = render(
+            vec![
+                column("users", "friend_ids", true, "uuid[]"),
+                column("users", "id", false, "uuid"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions::default(),
+        );
+        assert!(result.contains("import uuid\nfrom typing import TypedDict\n"));
+        assert!(result.contains("    friend_ids: list[uuid.UUID] | None\n"));
+        assert!(result.contains("    id: uuid.UUID\n"));
+
+        let result = render(
+            vec![column("users", "id", false, "integer")],
+            &ConversionOptions::default(),
+            &WriterOptions::default(),
+        );
+        assert!(!result.contains("import uuid"));
+    }
+
+    #[test]
+    fn writes_qualified_typing_names() {
+        let result = render(
+            vec![
+                column("users", "Any", false, "text"),
+                column("users", "email", true, "text"),
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("users", "id", false, "integer")
+                },
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                typing_import_style: TypingImportStyle::Qualified,
+                pk_newtypes: true,
+                ..Default::default()
+            },
+        );
+
+        let expected = indoc! {r#"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+
+            # This file requires Python >= 3.8
+            # If this is in error, please check the --minimum-python-version (-p) argument
+
+            import typing
+
+
+            UsersId = typing.NewType("UsersId", int)
+
+
+            class Users(typing.TypedDict):
+                Any: str
+                email: typing.Optional[str]
+                id: UsersId
+        "#};
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn writes_aliased_typing_names() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("Users"),
+            raw_name: String::from("users"),
+            properties: vec![PythonDictProperty {
+                name: String::from("email"),
                 nullable: true,
-                data_type: String::from("varchar"),
+                data_type: PythonDataType::String,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                typing_import_style: TypingImportStyle::Aliased,
+                ..Default::default()
             },
-        ];
+        );
 
-        let result = convert_table_column_definitions_to_python_dicts(table_column_definitions);
+        assert!(
+            result.contains("from typing import Optional as _Optional, TypedDict as _TypedDict\n")
+        );
+        assert!(result.contains("class Users(_TypedDict):\n    email: _Optional[str]\n"));
+    }
 
-        let expected = vec![
-            PythonTypedDict {
-                name: String::from("ATable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
-            },
-            PythonTypedDict {
-                name: String::from("BTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                }],
-            },
+    #[test]
+    fn writes_the_same_canonical_output_for_shuffled_inputs() {
+        let column = |table_name: &str, column_name: &str, data_type: &str| TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable: true,
+            data_type: String::from(data_type),
+            comment: Some(String::from("documented")),
+            distinct_values: None,
+            max_length: Some(10),
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        };
+        let columns = vec![
+            column("orders", "total", "numeric"),
+            column("orders", "id", "integer"),
+            column("users", "name", "varchar"),
+            column("users", "created_at", "timestamp with time zone"),
+            column("users", "id", "integer"),
+        ];
+        let shuffled_columns = vec![
+            column("users", "id", "integer"),
+            column("orders", "id", "integer"),
+            column("users", "created_at", "timestamp with time zone"),
+            column("users", "name", "varchar"),
+            column("orders", "total", "numeric"),
         ];
 
-        assert_eq!(result, expected)
-    }
+        let options = WriterOptions {
+            minimum_python_version: MinimumPythonVersion::Python3_8,
+            output_style: OutputStyle::Pydantic,
+            emit_tablename: true,
+            canonical: true,
+            ..Default::default()
+        };
+        let write = |columns: Vec<TableColumnDefinition>| {
+            write_python_dicts_to_str(
+                convert_table_column_definitions_to_python_dicts(
+                    columns,
+                    &ConversionOptions::default(),
+                ),
+                &options,
+            )
+        };
 
-    fn generate_preamble(minimum_python_version: MinimumPythonVersion) -> String {
-        match minimum_python_version {
-            MinimumPythonVersion::Python3_6 => indoc! {"
-                # This file was generated by db-introspector-gadget
-                # https://github.com/sesgoe/db-introspector-gadget
+        let result = write(columns);
 
-                # This file requires Python >= 3.6
-                # If this is in error, please check the --minimum-python-version (-p) argument
+        assert_eq!(result, write(shuffled_columns));
+        assert!(result.contains(indoc! {"
+            import datetime
+            from typing import ClassVar, Optional
 
-                import datetime
-                from typing import Any, Optional, TypedDict
-            "},
-            MinimumPythonVersion::Python3_8 => indoc! {"
-                # This file was generated by db-introspector-gadget
-                # https://github.com/sesgoe/db-introspector-gadget
+            from pydantic import BaseModel, Field, StringConstraints
+            from typing_extensions import Annotated
+        "}));
+        assert!(result.contains(indoc! {r#"
+            class Users(BaseModel):
+                __tablename__: ClassVar[str] = "users"
+                created_at: Optional[datetime.datetime] = Field(default=None, description="documented")
+                id: Optional[int] = Field(default=None, description="documented")
+                name: Optional[Annotated[str, StringConstraints(max_length=10)]] = Field(default=None, description="documented")
+        "#}));
+    }
 
-                # This file requires Python >= 3.8
-                # If this is in error, please check the --minimum-python-version (-p) argument
+    #[test]
+    fn groups_all_standard_library_imports_together_for_canonical_output() {
+        let result = render(
+            vec![
+                column("orders", "id", false, "uuid"),
+                TableColumnDefinition {
+                    max_length: Some(10),
+                    ..column("orders", "note", true, "varchar")
+                },
+                column("orders", "placed_at", true, "timestamp"),
+                column("orders", "total", false, "numeric"),
+            ],
+            &ConversionOptions {
+                decimal_as_decimal: true,
+                ..Default::default()
+            },
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                output_style: OutputStyle::Pydantic,
+                emit_json_encoder: true,
+                canonical: true,
+                ..Default::default()
+            },
+        );
 
-                import datetime
-                from typing import Any, Optional, TypedDict
-            "},
-            MinimumPythonVersion::Python3_10 => indoc! {"
-                # This file was generated by db-introspector-gadget
-                # https://github.com/sesgoe/db-introspector-gadget
+        assert!(result.contains(indoc! {"
+            import datetime
+            import decimal
+            import json
+            import uuid
+            from typing import Any, Optional
 
-                # This file requires Python >= 3.10
-                # If this is in error, please check the --minimum-python-version (-p) argument
+            from pydantic import BaseModel, StringConstraints
+            from typing_extensions import Annotated
 
-                import datetime
-                from typing import Any, TypedDict
-        "},
-        }
-        .to_owned()
+
+            class Orders(BaseModel):
+        "}));
     }
 
     #[test]
-    fn writes_single_dict_to_string() {
-        let dict = vec![PythonTypedDict {
-            name: String::from("SomeTable"),
+    fn writes_single_cell_output() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("ATable"),
+            raw_name: String::from("a_table"),
             properties: vec![
                 PythonDictProperty {
-                    name: String::from("column_one"),
+                    name: String::from("created_at"),
                     nullable: false,
-                    data_type: PythonDataType::String,
+                    data_type: PythonDataType::DateTime,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
-                    name: String::from("column_two"),
+                    name: String::from("extra"),
                     nullable: true,
-                    data_type: PythonDataType::String,
+                    data_type: PythonDataType::Any,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         }];
 
-        let result = write_python_dicts_to_str(dict, MinimumPythonVersion::Python3_10);
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                single_cell: true,
+                ..Default::default()
+            },
+        );
 
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_10);
-        let expected = formatdoc! {"
-            {preamble}
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
 
-            class SomeTable(TypedDict):
-                column_one: str
-                column_two: str | None
-            "};
+            # This file requires Python >= 3.8
+            # If this is in error, please check the --minimum-python-version (-p) argument
 
-        assert_eq!(result, expected)
+            import datetime; from typing import Any, Optional, TypedDict
+
+
+            class ATable(TypedDict):
+                created_at: datetime.datetime
+                extra: Optional[Any]
+        "};
+
+        assert_eq!(result, expected);
+
+        // every import lives on the single import line, ahead of all definitions
+        let import_lines = result
+            .lines()
+            .filter(|line| line.starts_with("import ") || line.starts_with("from "))
+            .collect::<Vec<&str>>();
+        assert_eq!(import_lines.len(), 1);
+        assert!(!result.contains("__future__"));
     }
 
     #[test]
-    fn writes_multiple_dict_to_string() {
-        let dicts = vec![
-            PythonTypedDict {
-                name: String::from("ATable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
-            },
-            PythonTypedDict {
-                name: String::from("BTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                }],
+    fn guards_everything_with_type_checking_for_types_only_module() {
+        let result = render(
+            vec![
+                column("users", "created_at", true, "timestamp"),
+                column("users", "id", false, "integer"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                types_only_module: true,
+                ..Default::default()
             },
-        ];
+        );
 
-        let result = write_python_dicts_to_str(dicts, MinimumPythonVersion::Python3_10);
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
 
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_10);
-        let expected = formatdoc! {"
-            {preamble}
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
 
-            class ATable(TypedDict):
-                column_one: str | None
+            from typing import TYPE_CHECKING
 
+            if TYPE_CHECKING:
+                import datetime
+                from typing import TypedDict
 
-            class BTable(TypedDict):
-                column_one: str
+
+                class Users(TypedDict):
+                    created_at: datetime.datetime | None
+                    id: int
         "};
 
-        assert_eq!(result, expected)
+        assert_eq!(result, expected);
+        // the only statements that run on import are the `TYPE_CHECKING` import and the `if` itself
+        let runtime_lines = result
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with([' ', '#']))
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            runtime_lines,
+            vec!["from typing import TYPE_CHECKING", "if TYPE_CHECKING:"]
+        );
     }
 
     #[test]
-    fn ignores_writing_dict_with_dollar_sign_in_name() {
-        let dicts = vec![
-            PythonTypedDict {
-                name: String::from("ATable$"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
-            },
-            PythonTypedDict {
-                name: String::from("BTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                }],
-            },
+    fn normalizes_trailing_whitespace_by_default() {
+        let results = [
+            render(
+                vec![
+                    column("users", "email", true, "text"),
+                    TableColumnDefinition {
+                        primary_key: true,
+                        ..column("users", "id", false, "integer")
+                    },
+                ],
+                &ConversionOptions::default(),
+                &WriterOptions::default(),
+            ),
+            write_python_dicts_to_str(vec![], &WriterOptions::default()),
         ];
 
-        let result = write_python_dicts_to_str(dicts, MinimumPythonVersion::Python3_10);
-
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_10);
-        let expected = formatdoc! {"
-            {preamble}
+        for result in results {
+            assert!(result.lines().all(|line| line == line.trim_end()));
+            assert!(result.ends_with('\n') && !result.ends_with("\n\n"));
+        }
+    }
 
-            class BTable(TypedDict):
-                column_one: str
-            "};
+    #[test]
+    fn keeps_trailing_whitespace_when_asked() {
+        let result = write_python_dicts_to_str(
+            vec![],
+            &WriterOptions {
+                keep_trailing_whitespace: true,
+                ..Default::default()
+            },
+        );
 
-        assert_eq!(result, expected)
+        assert!(result.ends_with("\n\n\n"));
     }
 
     #[test]
-    fn ignores_writing_dict_name_starts_with_number() {
+    fn writes_one_module_per_dict_with_package_files() {
         let dicts = vec![
             PythonTypedDict {
-                name: String::from("1Table"),
+                name: String::from("UserAccount"),
+                raw_name: String::from("user_account"),
                 properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
+                    name: String::from("id"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 }],
             },
             PythonTypedDict {
-                name: String::from("BTable"),
+                name: String::from("Order"),
+                raw_name: String::from("order"),
                 properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
+                    name: String::from("total"),
+                    nullable: true,
+                    data_type: PythonDataType::Float,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 }],
             },
         ];
 
-        let result = write_python_dicts_to_str(dicts, MinimumPythonVersion::Python3_10);
+        let files = write_python_dicts_to_files(dicts, &WriterOptions::default());
 
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_10);
-        let expected = formatdoc! {"
-            {preamble}
+        let paths = files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            paths,
+            vec!["order.py", "user_account.py", "__init__.py", "py.typed"]
+        );
 
-            class BTable(TypedDict):
-                column_one: str
-        "};
+        let preamble = generate_preamble(
+            MinimumPythonVersion::Python3_10,
+            "from typing import TypedDict",
+        );
+        assert_eq!(
+            files[1].contents,
+            formatdoc! {"
+                {preamble}
 
-        assert_eq!(result, expected)
+                class UserAccount(TypedDict):
+                    id: int
+            "}
+        );
+
+        assert_eq!(
+            files[2].contents,
+            indoc! {r#"
+                # This file was generated by db-introspector-gadget
+                # https://github.com/sesgoe/db-introspector-gadget
+
+                from .order import Order
+                from .user_account import UserAccount
+
+                __all__ = ["Order", "UserAccount"]
+            "#}
+        );
+        assert_eq!(files[3].contents, "");
     }
 
     #[test]
-    fn first_dict_backwards_compat() {
-        let dicts = vec![
-            PythonTypedDict {
-                name: String::from("ATable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("1column"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
+    fn writes_init_stub_with_explicit_re_exports() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("UserAccount"),
+            raw_name: String::from("user_account"),
+            properties: vec![PythonDictProperty {
+                name: String::from("id"),
+                nullable: false,
+                data_type: PythonDataType::Integer,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }];
+
+        let files = write_python_dicts_to_files(
+            dicts,
+            &WriterOptions {
+                init_stub: true,
+                ..Default::default()
             },
-            PythonTypedDict {
-                name: String::from("BTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                }],
+        );
+
+        let init_stub = files
+            .iter()
+            .find(|file| file.path == "__init__.pyi")
+            .unwrap();
+        assert_eq!(
+            init_stub.contents,
+            indoc! {"
+                # This file was generated by db-introspector-gadget
+                # https://github.com/sesgoe/db-introspector-gadget
+
+                from .user_account import UserAccount as UserAccount
+            "}
+        );
+    }
+
+    #[test]
+    fn writes_a_registry_of_table_names_to_classes() {
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            vec![
+                column("order_items", "1st", true, "text"),
+                column("order_items", "id", false, "integer"),
+                column("users", "id", false, "integer"),
+            ],
+            &ConversionOptions::default(),
+        );
+
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                emit_registry: true,
+                ..Default::default()
             },
-        ];
+        );
+        assert!(result.ends_with(indoc! {r#"
 
-        let result = write_python_dicts_to_str(dicts, MinimumPythonVersion::Python3_10);
 
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_10);
+            MODELS: dict[str, type] = {
+                "order_items": OrderItems,
+                "users": Users,
+            }
+        "#}));
 
-        // remember that in format strings {{ and }} are escaped `{` and `}` respectively
-        let expected = formatdoc! {"
-            {preamble}
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                emit_registry: true,
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                ..Default::default()
+            },
+        );
+        assert!(result.contains("from typing import Optional, TypedDict, Dict\n"));
+        assert!(result.contains("MODELS: Dict[str, type] = {\n"));
 
-            ATable = TypedDict('ATable', {{
-                '1column': str | None
-            }})
+        let files = write_python_dicts_to_files(
+            dicts,
+            &WriterOptions {
+                emit_registry: true,
+                ..Default::default()
+            },
+        );
+        let contents = |path: &str| {
+            files
+                .iter()
+                .find(|file| file.path == path)
+                .unwrap()
+                .contents
+                .as_str()
+        };
+        assert!(contents("__init__.py").contains("MODELS: dict[str, type] = {\n"));
+        assert!(!contents("users.py").contains("MODELS"));
+    }
 
+    #[test]
+    fn writes_a_json_encoder_for_the_types_in_use() {
+        let write = |table_column_definitions| {
+            render(
+                table_column_definitions,
+                &ConversionOptions {
+                    decimal_as_decimal: true,
+                    ..Default::default()
+                },
+                &WriterOptions {
+                    emit_json_encoder: true,
+                    ..Default::default()
+                },
+            )
+        };
 
-            class BTable(TypedDict):
-                column_one: str
-            "};
+        let result = write(vec![
+            column("orders", "due", true, "date"),
+            column("orders", "id", false, "uuid"),
+            column("orders", "placed_at", false, "timestamp"),
+            column("orders", "total", false, "numeric"),
+        ]);
+        assert!(result.contains("import datetime\nimport decimal\nimport json\nimport uuid\n"));
+        assert!(result.ends_with(indoc! {"
 
-        assert_eq!(result, expected)
+
+            class RowJSONEncoder(json.JSONEncoder):
+                def default(self, o: Any) -> Any:
+                    if isinstance(o, (datetime.datetime, datetime.date)):
+                        return o.isoformat()
+                    if isinstance(o, decimal.Decimal):
+                        return str(o)
+                    if isinstance(o, uuid.UUID):
+                        return str(o)
+                    return super().default(o)
+        "}));
+
+        let result = write(vec![
+            column("users", "born", true, "date"),
+            column("users", "id", false, "integer"),
+        ]);
+        assert!(result.ends_with(indoc! {"
+            class RowJSONEncoder(json.JSONEncoder):
+                def default(self, o: Any) -> Any:
+                    if isinstance(o, datetime.date):
+                        return o.isoformat()
+                    return super().default(o)
+        "}));
     }
 
     #[test]
-    fn supports_python_3_6() {
-        let dicts = vec![
-            PythonTypedDict {
-                name: String::from("ATable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("1column"),
-                    nullable: true,
-                    data_type: PythonDataType::String,
-                }],
+    fn writes_aware_datetimes_for_all_timestamp_columns() {
+        let table_column_definitions = vec![
+            TableColumnDefinition {
+                table_name: String::from("events"),
+                column_name: String::from("created_at"),
+                nullable: false,
+                data_type: String::from("timestamp without time zone"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
             },
-            PythonTypedDict {
-                name: String::from("BTable"),
-                properties: vec![PythonDictProperty {
-                    name: String::from("column_one"),
-                    nullable: false,
-                    data_type: PythonDataType::String,
-                }],
+            TableColumnDefinition {
+                table_name: String::from("events"),
+                column_name: String::from("updated_at"),
+                nullable: false,
+                data_type: String::from("timestamp with time zone"),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
             },
         ];
 
-        let result = write_python_dicts_to_str(dicts, MinimumPythonVersion::Python3_6);
+        let dicts = convert_table_column_definitions_to_python_dicts(
+            table_column_definitions,
+            &ConversionOptions::default(),
+        );
 
-        let preamble = generate_preamble(MinimumPythonVersion::Python3_6);
+        let result = write_python_dicts_to_str(
+            dicts.clone(),
+            &WriterOptions {
+                datetime_aware: true,
+                ..Default::default()
+            },
+        );
 
-        // remember that in format strings {{ and }} are escaped `{` and `}` respectively
-        let expected = formatdoc! {"
-            {preamble}
+        let expected = indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
 
-            ATable = TypedDict('ATable', {{
-                '1column': Optional[str]
-            }})
+            # This file requires Python >= 3.10
+            # If this is in error, please check the --minimum-python-version (-p) argument
 
+            # All datetime.datetime fields are typed as timezone-aware,
+            # including columns stored without a time zone, whose values are in UTC
 
-            BTable = TypedDict('BTable', {{
-                'column_one': str
-            }})
+            import datetime
+            from typing import TypedDict, Annotated, TypeAlias
+
+
+            AwareDatetime: TypeAlias = Annotated[datetime.datetime, \"UTC\"]
+
+
+            class Events(TypedDict):
+                created_at: AwareDatetime
+                updated_at: AwareDatetime
         "};
 
-        assert_eq!(result, expected)
+        assert_eq!(result, expected);
+
+        // `typing.Annotated` was only added in Python 3.9
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                datetime_aware: true,
+                minimum_python_version: MinimumPythonVersion::Python3_8,
+                ..Default::default()
+            },
+        );
+        assert!(result.contains("from typing_extensions import Annotated\n"));
+        assert!(result.contains("AwareDatetime = Annotated[datetime.datetime, \"UTC\"]\n"));
+        assert!(result.contains("    created_at: AwareDatetime\n"));
+    }
+
+    #[test]
+    fn writes_generated_from_header() {
+        let dicts = vec![PythonTypedDict {
+            name: String::from("ATable"),
+            raw_name: String::from("a_table"),
+            properties: vec![PythonDictProperty {
+                name: String::from("column_one"),
+                nullable: false,
+                data_type: PythonDataType::String,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }];
+
+        let result = write_python_dicts_to_str(
+            dicts,
+            &WriterOptions {
+                generated_from: Some(String::from("public@db.example.com")),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.starts_with(indoc! {"
+            # This file was generated by db-introspector-gadget
+            # https://github.com/sesgoe/db-introspector-gadget
+            # generated-from: public@db.example.com
+
+            # This file requires Python >= 3.10
+        "}));
+    }
+
+    #[test]
+    fn writes_column_count_comments() {
+        let result = render(
+            vec![
+                column("codes", "code", false, "text"),
+                column("users", "email", false, "text"),
+                column("users", "id", false, "integer"),
+                column("users", "name", false, "text"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                output_style: OutputStyle::Dataclass,
+                column_count_comment: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("# 1 column\n@dataclass\nclass Codes:\n"));
+        assert!(result.contains("# 3 columns\n@dataclass\nclass Users:\n"));
+    }
+
+    #[test]
+    fn writes_keys_comments_with_columns_in_order() {
+        let result = render(
+            vec![
+                column("users", "email", true, "text"),
+                column("users", "id", false, "integer"),
+                column("users", "name", false, "text"),
+            ],
+            &ConversionOptions::default(),
+            &WriterOptions {
+                keys_comment: true,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.contains("# keys: email, id, name\nclass Users(TypedDict):\n    email: "));
     }
 }
@@ -1,18 +1,43 @@
+use std::str::FromStr;
+
+use convert_case::{Case, Casing};
 use itertools::{Itertools, Position};
 
-use crate::MinimumPythonVersion;
+use crate::{db_introspector::CheckBound, MinimumPythonVersion, PydanticJson, TypedDictNameSource};
+
+/// The alias `--datetime-aware` declares for `Annotated[datetime.datetime, "UTC"]`
+pub(crate) const AWARE_DATETIME_ALIAS: &str = "AwareDatetime";
 
 /// This enum represents all the Python types we can output
 /// `Any` is included as a catch-all to handle unknown database types.
+///
+/// `AwareDateTime` is a `DateTime` typed as the timezone-aware `AwareDatetime` alias with `--datetime-aware`,
+/// `List` is an array column of its element type, e.g. `list[list[int]]` for a Postgres `integer[][]`,
+/// `Literal` holds the Python source representations of its values, e.g. `"active"`,
+/// `NewType` a distinct type declared over `base` with `--pk-newtypes`, e.g. `UsersId`,
+/// and `Nested` the name of another generated class, from `--group-columns-by-prefix`
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub(crate) enum PythonDataType {
     String,
     Integer,
     Float,
+    Decimal,
     Boolean,
     DateTime,
+    AwareDateTime,
     Date,
+    Time,
+    Interval,
     Binary,
+    Json,
+    Uuid,
+    List(Box<PythonDataType>),
+    Literal(Vec<String>),
+    NewType {
+        name: String,
+        base: Box<PythonDataType>,
+    },
+    Nested(String),
     Any,
 }
 
@@ -23,29 +48,369 @@ impl PythonDataType {
             PythonDataType::String => "str",
             PythonDataType::Integer => "int",
             PythonDataType::Float => "float",
+            PythonDataType::Decimal => "decimal.Decimal",
             PythonDataType::Boolean => "bool",
             PythonDataType::DateTime => "datetime.datetime",
+            PythonDataType::AwareDateTime => AWARE_DATETIME_ALIAS,
             PythonDataType::Date => "datetime.date",
+            PythonDataType::Time => "datetime.time",
+            PythonDataType::Interval => "datetime.timedelta",
             PythonDataType::Binary => "bytes",
+            PythonDataType::Json => "str", // drivers disagree on whether json is decoded, so it's left as text
+            PythonDataType::Uuid => "uuid.UUID",
+            PythonDataType::List(element) => {
+                return format!("list[{}]", element.as_primitive_type_str())
+            }
+            PythonDataType::Literal(values) => return format!("Literal[{}]", values.join(", ")),
+            PythonDataType::NewType { name, .. } => return name.clone(),
+            PythonDataType::Nested(name) => return name.clone(),
             PythonDataType::Any => "Any",
         }
         .to_string()
     }
+
+    /// Convert a `PythonDataType` into the Polars dtype used for it in a `--output-style polars` schema dict
+    ///
+    /// Polars columns are always nullable, so nullability doesn't change the dtype
+    pub(crate) fn as_polars_dtype_str(&self) -> String {
+        match self {
+            PythonDataType::String => "pl.Utf8",
+            PythonDataType::Integer => "pl.Int64",
+            PythonDataType::Float => "pl.Float64",
+            PythonDataType::Decimal => "pl.Decimal",
+            PythonDataType::Boolean => "pl.Boolean",
+            PythonDataType::DateTime => "pl.Datetime",
+            PythonDataType::AwareDateTime => "pl.Datetime(time_zone=\"UTC\")",
+            PythonDataType::Date => "pl.Date",
+            PythonDataType::Time => "pl.Time",
+            PythonDataType::Interval => "pl.Duration",
+            PythonDataType::Binary => "pl.Binary",
+            PythonDataType::Json => "pl.Utf8",
+            PythonDataType::Uuid => "pl.Utf8", // polars has no uuid dtype
+            PythonDataType::List(element) => {
+                return format!("pl.List({})", element.as_polars_dtype_str())
+            }
+            // e.g. `Literal[0, 1]` from `--bool-as-int-literal`, every other literal is a string
+            PythonDataType::Literal(values) if values.iter().all(|v| v.parse::<i64>().is_ok()) => {
+                "pl.Int64"
+            }
+            PythonDataType::Literal(_) => "pl.Utf8",
+            PythonDataType::NewType { base, .. } => return base.as_polars_dtype_str(),
+            PythonDataType::Nested(name) => {
+                return format!("pl.Struct({})", as_polars_schema_name(name))
+            }
+            PythonDataType::Any => "pl.Object",
+        }
+        .to_string()
+    }
+
+    /// Convert a `PythonDataType` into the SQLAlchemy column type it is stored as in a `--output-style sqlalchemy` model
+    pub(crate) fn as_sqlalchemy_type_str(&self) -> String {
+        match self {
+            PythonDataType::String => "String",
+            PythonDataType::Integer => "Integer",
+            PythonDataType::Float => "Float",
+            PythonDataType::Decimal => "Numeric",
+            PythonDataType::Boolean => "Boolean",
+            PythonDataType::DateTime | PythonDataType::AwareDateTime => "DateTime",
+            PythonDataType::Date => "Date",
+            PythonDataType::Time => "Time",
+            PythonDataType::Interval => "Interval",
+            PythonDataType::Binary => "LargeBinary",
+            PythonDataType::Json => "String", // typed as `str`, see `as_primitive_type_str`
+            PythonDataType::Uuid => "Uuid",
+            PythonDataType::List(element) => {
+                return format!("ARRAY({})", element.as_sqlalchemy_type_str())
+            }
+            // e.g. `Literal[0, 1]` from `--bool-as-int-literal`, every other literal is a string
+            PythonDataType::Literal(values) if values.iter().all(|v| v.parse::<i64>().is_ok()) => {
+                "Integer"
+            }
+            PythonDataType::Literal(_) => "String",
+            PythonDataType::NewType { base, .. } => return base.as_sqlalchemy_type_str(),
+            PythonDataType::Nested(_) | PythonDataType::Any => "JSON",
+        }
+        .to_string()
+    }
+
+    /// Whether SQLAlchemy's default type map can tell the column type from the `Mapped[...]` annotation alone,
+    /// which it can't for generic, `Literal`, `NewType`, `Any` and alias annotations
+    pub(crate) fn is_sqlalchemy_annotation_mapped(&self) -> bool {
+        !matches!(
+            self,
+            PythonDataType::AwareDateTime
+                | PythonDataType::List(_)
+                | PythonDataType::Literal(_)
+                | PythonDataType::NewType { .. }
+                | PythonDataType::Nested(_)
+                | PythonDataType::Any
+        )
+    }
+
+    /// Convert a `PythonDataType` into its source code type representation for the given Python version,
+    /// which only differs from `as_primitive_type_str` for lists, since `list` isn't subscriptable before Python 3.9
+    pub(crate) fn as_versioned_type_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+    ) -> String {
+        match self {
+            PythonDataType::List(element)
+                if minimum_python_version < MinimumPythonVersion::Python3_10 =>
+            {
+                format!(
+                    "List[{}]",
+                    element.as_versioned_type_str(minimum_python_version)
+                )
+            }
+            _ => self.as_primitive_type_str(),
+        }
+    }
+
+    /// Whether this type is, or is built on, the given type, e.g. for `uuid.UUID` which needs `import uuid`
+    pub(crate) fn uses(&self, data_type: &PythonDataType) -> bool {
+        match self {
+            PythonDataType::AwareDateTime => {
+                matches!(
+                    data_type,
+                    PythonDataType::AwareDateTime | PythonDataType::DateTime
+                )
+            }
+            PythonDataType::List(element) => element.uses(data_type),
+            PythonDataType::NewType { base, .. } => base.uses(data_type),
+            _ => self == data_type,
+        }
+    }
+
+    /// Whether a database `data_type` stores exact numbers, e.g. MySQL `decimal`, Postgres `numeric`, or arrays of them
+    pub(crate) fn is_exact_numeric(data_type: &str) -> bool {
+        matches!(data_type.trim_end_matches("[]"), "decimal" | "numeric")
+    }
+
+    /// Replaces the `float` of an exact numeric type with `decimal.Decimal`, for `--decimal-as-decimal`
+    pub(crate) fn with_decimals(self) -> PythonDataType {
+        match self {
+            PythonDataType::Float => PythonDataType::Decimal,
+            PythonDataType::List(element) => {
+                PythonDataType::List(Box::new(element.with_decimals()))
+            }
+            other => other,
+        }
+    }
+
+    /// Replaces a (possibly nested list of) `datetime.datetime` with the `AwareDatetime` alias, for `--datetime-aware`
+    pub(crate) fn with_aware_datetimes(self) -> PythonDataType {
+        match self {
+            PythonDataType::DateTime => PythonDataType::AwareDateTime,
+            PythonDataType::List(element) => {
+                PythonDataType::List(Box::new(element.with_aware_datetimes()))
+            }
+            other => other,
+        }
+    }
+
+    /// Replaces the element type of a (possibly nested) list, or the type itself if it isn't a list,
+    /// e.g. for the labels of a Postgres enum array
+    pub(crate) fn with_element(self, element: PythonDataType) -> PythonDataType {
+        match self {
+            PythonDataType::List(inner) => {
+                PythonDataType::List(Box::new(inner.with_element(element)))
+            }
+            _ => element,
+        }
+    }
+
+    /// Whether a `NewType` can be declared over this type, which has to be a class
+    pub(crate) fn supports_new_type(&self) -> bool {
+        !matches!(
+            self,
+            PythonDataType::AwareDateTime
+                | PythonDataType::List(_)
+                | PythonDataType::Literal(_)
+                | PythonDataType::NewType { .. }
+                | PythonDataType::Any
+        )
+    }
+
+    /// Builds a `Literal[...]` type from the distinct values sampled from a categorical column,
+    /// or `None` if there are none or too many of them for the column to plausibly be an enum
+    pub(crate) fn literal_from_distinct_values(
+        distinct_values: &[String],
+        max_values: usize,
+    ) -> Option<PythonDataType> {
+        if distinct_values.is_empty() || distinct_values.len() > max_values {
+            return None;
+        }
+
+        Some(PythonDataType::Literal(
+            distinct_values
+                .iter()
+                .sorted()
+                .dedup()
+                .map(|value| as_python_string_literal(value))
+                .collect(),
+        ))
+    }
+
+    /// Builds a `Literal[...]` type from the members of a MySQL `enum('a','b')` column type, in their declared order,
+    /// or `None` if it isn't an enum. Quotes inside members are doubled, e.g. `enum('it''s')`
+    pub(crate) fn literal_from_mysql_enum(column_type: &str) -> Option<PythonDataType> {
+        let members = column_type
+            .strip_prefix("enum(")
+            .or_else(|| column_type.strip_prefix("ENUM("))?
+            .strip_suffix(')')?;
+
+        let mut values = vec![];
+        let mut chars = members.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    let mut value = String::new();
+                    loop {
+                        match chars.next()? {
+                            '\'' if chars.peek() == Some(&'\'') => {
+                                chars.next();
+                                value.push('\'');
+                            }
+                            '\'' => break,
+                            c => value.push(c),
+                        }
+                    }
+                    values.push(as_python_string_literal(&value));
+                }
+                ',' | ' ' => {}
+                _ => return None,
+            }
+        }
+
+        (!values.is_empty()).then_some(PythonDataType::Literal(values))
+    }
+}
+
+/// Parses a `PythonDataType` from its variant name (case-insensitive), which is how the CLI refers to them,
+/// e.g. `--include-column-types string,integer`
+impl FromStr for PythonDataType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "string" => Ok(PythonDataType::String),
+            "integer" => Ok(PythonDataType::Integer),
+            "float" => Ok(PythonDataType::Float),
+            "decimal" => Ok(PythonDataType::Decimal),
+            "boolean" => Ok(PythonDataType::Boolean),
+            "datetime" => Ok(PythonDataType::DateTime),
+            "date" => Ok(PythonDataType::Date),
+            "time" => Ok(PythonDataType::Time),
+            "interval" => Ok(PythonDataType::Interval),
+            "binary" => Ok(PythonDataType::Binary),
+            "json" => Ok(PythonDataType::Json),
+            "uuid" => Ok(PythonDataType::Uuid),
+            "list" => Ok(PythonDataType::List(Box::new(PythonDataType::Any))),
+            "literal" => Ok(PythonDataType::Literal(vec![])),
+            "any" => Ok(PythonDataType::Any),
+            _ => Err(format!(
+                "unknown type `{name}`, expected one of: string, integer, float, decimal, boolean, datetime, date, time, interval, binary, json, uuid, list, literal, any"
+            )),
+        }
+    }
+}
+
+/// A database server version, e.g. `14` or `8.0.32`, of which `--dialect-version` compares the major and minor parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct DialectVersion {
+    pub(crate) major: u32,
+    pub(crate) minor: u32,
+}
+
+impl DialectVersion {
+    const fn new(major: u32, minor: u32) -> Self {
+        DialectVersion { major, minor }
+    }
+}
+
+/// Parses a version like `14`, `9.4`, or `8.0.32`, ignoring anything past the minor version
+impl FromStr for DialectVersion {
+    type Err = String;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let mut parts = version.trim().split('.');
+        let mut next_part = |default: Option<u32>| match (parts.next(), default) {
+            (Some(part), _) => part.parse::<u32>().ok(),
+            (None, default) => default,
+        };
+
+        match (next_part(None), next_part(Some(0))) {
+            (Some(major), Some(minor)) => Ok(DialectVersion::new(major, minor)),
+            _ => Err(format!(
+                "invalid version `{version}`, expected e.g. `14`, `9.4`, or `8.0.32`"
+            )),
+        }
+    }
+}
+
+/// Postgres types that only exist from a given server version on, which `--dialect-version` types as `Any` below it
+const VERSION_GATED_TYPES: &[(&str, DialectVersion)] = &[
+    ("jsonb", DialectVersion::new(9, 4)),
+    ("pg_lsn", DialectVersion::new(9, 4)),
+    ("int4multirange", DialectVersion::new(14, 0)),
+    ("int8multirange", DialectVersion::new(14, 0)),
+    ("nummultirange", DialectVersion::new(14, 0)),
+    ("tsmultirange", DialectVersion::new(14, 0)),
+    ("tstzmultirange", DialectVersion::new(14, 0)),
+    ("datemultirange", DialectVersion::new(14, 0)),
+];
+
+impl PythonDataType {
+    /// Converts a SQL Server `DATA_TYPE` like `From<String>` does, except for `tinyint`, which is a 0 to 255
+    /// integer on SQL Server rather than MySQL's boolean
+    pub(crate) fn from_sql_server(data_type: String) -> PythonDataType {
+        match data_type.as_str() {
+            "tinyint" => PythonDataType::Integer,
+            _ => PythonDataType::from(data_type),
+        }
+    }
+
+    /// Converts a `data_type` like `From<String>` does, except that types the server doesn't have
+    /// at `dialect_version` are typed as `Any`. Without a version, every type is assumed to exist
+    pub(crate) fn from_versioned(
+        data_type: String,
+        dialect_version: Option<DialectVersion>,
+    ) -> PythonDataType {
+        let introduced_in = VERSION_GATED_TYPES
+            .iter()
+            .find(|(name, _)| *name == data_type)
+            .map(|(_, introduced_in)| *introduced_in);
+
+        match (introduced_in, dialect_version) {
+            (Some(introduced_in), Some(dialect_version)) if dialect_version < introduced_in => {
+                PythonDataType::Any
+            }
+            _ => PythonDataType::from(data_type),
+        }
+    }
 }
 
 /// This is the primary way we convert the database INFORMATION_SCHEMA.TABLE_COLUMNS `data_type` string column
 /// into given Python data types
 impl From<String> for PythonDataType {
     fn from(data_type: String) -> Self {
+        // arrays are spelled with one `[]` per dimension, e.g. `boolean[]` or `integer[][]`
+        if let Some(element) = data_type.strip_suffix("[]") {
+            return PythonDataType::List(Box::new(PythonDataType::from(element.to_string())));
+        }
+
         match data_type.as_str() {
             //both
             "text" => PythonDataType::String,
             "date" => PythonDataType::Date,
+            "time" => PythonDataType::Time, // postgres spells it `time without time zone`
+            "json" => PythonDataType::Json,
             "bigint" => PythonDataType::Integer,
 
             // mysql
-            "varchar" | "longtext" | "json" | "char" | "mediumtext" | "enum" | "set"
-            | "tinytext" => PythonDataType::String,
+            "varchar" | "longtext" | "char" | "mediumtext" | "enum" | "set" | "tinytext" => {
+                PythonDataType::String
+            }
             "int" | "smallint" | "mediumint" => PythonDataType::Integer,
             "float" | "double" | "decimal" => PythonDataType::Float,
             "tinyint" => PythonDataType::Boolean,
@@ -54,16 +419,56 @@ impl From<String> for PythonDataType {
 
             // postgres
             "integer" => PythonDataType::Integer,
+            "jsonb" => PythonDataType::Json,
+            "uuid" => PythonDataType::Uuid,
             "boolean" => PythonDataType::Boolean,
-            "character varying" | "character" | "jsonb" | "USER-DEFINED" => PythonDataType::String, // user-defined are typically enums for type-inference purposes
+            "character varying" | "character" | "USER-DEFINED" => PythonDataType::String, // user-defined are typically enums, see `resolve_postgres_user_defined_type`
             "double precision" | "numeric" => PythonDataType::Float,
             "timestamp with time zone" | "timestamp without time zone" => PythonDataType::DateTime,
+            "time without time zone" => PythonDataType::Time,
+            "interval" => PythonDataType::Interval,
+            "pg_lsn" => PythonDataType::String, // e.g. `16/B374D848`
+            // ranges are read as their text representation, e.g. `[1,10)`
+            "int4range" | "int8range" | "numrange" | "tsrange" | "tstzrange" | "daterange"
+            | "int4multirange" | "int8multirange" | "nummultirange" | "tsmultirange"
+            | "tstzmultirange" | "datemultirange" => PythonDataType::String,
+
+            // sql server, whose `tinyint` is mapped by `from_sql_server`
+            "nvarchar" | "nchar" | "ntext" | "xml" => PythonDataType::String,
+            "uniqueidentifier" => PythonDataType::Uuid,
+            "bit" => PythonDataType::Boolean,
+            "datetime2" | "smalldatetime" | "datetimeoffset" => PythonDataType::DateTime,
+            "money" | "smallmoney" | "real" => PythonDataType::Float,
+            "image" => PythonDataType::Binary,
 
             _ => PythonDataType::Any,
         }
     }
 }
 
+/// Builds the name of the `--output-style polars` schema dict of a class, e.g. `USER_ACCOUNT_SCHEMA` for `UserAccount`
+pub(crate) fn as_polars_schema_name(class_name: &str) -> String {
+    format!("{}_SCHEMA", class_name.to_case(Case::UpperSnake))
+}
+
+/// Escapes arbitrary text, like a column comment from the database, into a double-quoted Python string literal
+pub(crate) fn as_python_string_literal(value: &str) -> String {
+    let mut result = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(format!("\\x{:02x}", c as u32).as_str()),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
 /// Represents a Python `TypedDict` property
 /// ```text
 /// class SomeTypedDict(TypedDict):
@@ -73,11 +478,19 @@ impl From<String> for PythonDataType {
 ///     name                 |   nullable
 ///                          data_type
 /// ```
+///
+/// `comment` holds the database column comment, which some output styles render as documentation,
+/// and `max_length` the length limit of a character column and `check_bounds` the bounds `CHECK` constraints
+/// put on a numeric one in a `--from-sql-file` schema, which the Pydantic style validates
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub(crate) struct PythonDictProperty {
     pub(crate) name: String,
     pub(crate) nullable: bool,
     pub(crate) data_type: PythonDataType,
+    pub(crate) comment: Option<String>,
+    pub(crate) max_length: Option<u64>,
+    pub(crate) check_bounds: Vec<CheckBound>,
+    pub(crate) primary_key: bool,
 }
 
 impl PythonDictProperty {
@@ -85,20 +498,87 @@ impl PythonDictProperty {
     pub(crate) fn as_property_type_str(
         &self,
         minimum_python_version: MinimumPythonVersion,
+    ) -> String {
+        self.as_nullable_type_str(
+            self.data_type.as_versioned_type_str(minimum_python_version),
+            minimum_python_version,
+        )
+    }
+
+    /// Builds a string representing the type of the given `PythonDictProperty` in a Pydantic model,
+    /// where json columns can be parsed into Python objects instead of being left as `str`,
+    /// and character and numeric columns are validated against their maximum length and `CHECK` bounds
+    /// like the database would
+    pub(crate) fn as_pydantic_property_type_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+        pydantic_json: Option<PydanticJson>,
+    ) -> String {
+        match (&self.data_type, pydantic_json, self.max_length) {
+            (PythonDataType::Json, Some(pydantic_json), _) => self.as_nullable_type_str(
+                pydantic_json.as_type_str(minimum_python_version),
+                minimum_python_version,
+            ),
+            // `Annotated[...] | None`, since a `None` wouldn't pass the constraint
+            (PythonDataType::String, _, Some(max_length)) => self.as_nullable_type_str(
+                format!("Annotated[str, StringConstraints(max_length={max_length})]"),
+                minimum_python_version,
+            ),
+            (data_type, _, _) if self.has_check_bounds() => self.as_nullable_type_str(
+                format!(
+                    "Annotated[{}, Field({})]",
+                    data_type.as_versioned_type_str(minimum_python_version),
+                    self.check_bounds
+                        .iter()
+                        .map(|bound| format!("{}={}", bound.keyword, bound.value))
+                        .join(", ")
+                ),
+                minimum_python_version,
+            ),
+            _ => self.as_property_type_str(minimum_python_version),
+        }
+    }
+
+    /// Whether this is a numeric property with bounds from `CHECK` constraints to validate
+    pub(crate) fn has_check_bounds(&self) -> bool {
+        matches!(
+            self.data_type,
+            PythonDataType::Integer | PythonDataType::Float | PythonDataType::Decimal
+        ) && !self.check_bounds.is_empty()
+    }
+
+    /// Wraps the given primitive type in `T | None` or `Optional[T]` if this property is nullable
+    fn as_nullable_type_str(
+        &self,
+        primitive_type_str: String,
+        minimum_python_version: MinimumPythonVersion,
     ) -> String {
         if self.nullable {
-            match minimum_python_version {
-                MinimumPythonVersion::Python3_10 => {
-                    format!("{} | None", self.data_type.as_primitive_type_str())
-                }
-                _ => format!("Optional[{}]", self.data_type.as_primitive_type_str()),
+            if minimum_python_version >= MinimumPythonVersion::Python3_10 {
+                format!("{} | None", primitive_type_str)
+            } else {
+                format!("Optional[{}]", primitive_type_str)
             }
         } else {
-            self.data_type.as_primitive_type_str()
+            primitive_type_str
         }
     }
 }
 
+impl PydanticJson {
+    /// Convert a `PydanticJson` into the source code type representation of a json column
+    pub(crate) fn as_type_str(&self, minimum_python_version: MinimumPythonVersion) -> String {
+        match (self, minimum_python_version) {
+            (PydanticJson::Json, _) => "Json[Any]",
+            (PydanticJson::Dict, version) if version >= MinimumPythonVersion::Python3_10 => {
+                "dict[str, Any]"
+            }
+            (PydanticJson::Dict, _) => "Dict[str, Any]", // `dict` isn't subscriptable before Python 3.9
+        }
+        .to_string()
+    }
+}
+
 /// This enum represents whether or not backward-compatible `TypedDict`
 /// syntax is enabled.
 ///
@@ -125,6 +605,96 @@ impl From<bool> for ForcedBackwardCompat {
     }
 }
 
+/// This enum represents whether every key of a `TypedDict` is required, which is the default,
+/// or whether every key may be left out, e.g. for partial-update payloads, spelled either as
+/// `total=False` on the whole dict (`Partial`) or as `NotRequired[...]` on each key (`PartialPerKey`)
+///
+/// `NullableNotRequired` only lets the keys of nullable columns be left out, as `NotRequired[T]` rather
+/// than `T | None`, for rows that leave out their NULL values instead of holding `None`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Totality {
+    #[default]
+    Total,
+    Partial,
+    PartialPerKey,
+    NullableNotRequired,
+}
+
+impl Totality {
+    /// Builds the trailing `total=...` argument of a `TypedDict` definition, if one is needed
+    fn as_argument_str(&self) -> &'static str {
+        match self {
+            Totality::Total | Totality::PartialPerKey | Totality::NullableNotRequired => "",
+            Totality::Partial => ", total=False",
+        }
+    }
+
+    /// Whether keys are marked with `NotRequired[...]`, which then has to be imported
+    pub(crate) fn marks_keys(&self) -> bool {
+        matches!(
+            self,
+            Totality::PartialPerKey | Totality::NullableNotRequired
+        )
+    }
+
+    /// Builds the type of a property as a key of a `TypedDict`, marked with `NotRequired[...]` if needed
+    fn as_key_type_str(
+        &self,
+        property: &PythonDictProperty,
+        minimum_python_version: MinimumPythonVersion,
+    ) -> String {
+        match self {
+            Totality::PartialPerKey => format!(
+                "NotRequired[{}]",
+                property.as_property_type_str(minimum_python_version)
+            ),
+            Totality::NullableNotRequired if property.nullable => {
+                let present_property = PythonDictProperty {
+                    nullable: false,
+                    ..property.clone()
+                };
+                format!(
+                    "NotRequired[{}]",
+                    present_property.as_property_type_str(minimum_python_version)
+                )
+            }
+            _ => property.as_property_type_str(minimum_python_version),
+        }
+    }
+}
+
+/// Options for the arguments of the `@dataclass(...)` decorator in the dataclass output style
+///
+/// - `frozen=True` makes instances immutable, which matches the read-only row use case
+/// - `slots=True` generates `__slots__` to reduce memory usage (requires Python >= 3.10)
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct DataclassOptions {
+    pub(crate) frozen: bool,
+    pub(crate) slots: bool,
+}
+
+impl DataclassOptions {
+    /// Builds the `@dataclass` decorator line for these options
+    pub(crate) fn as_decorator_str(&self) -> String {
+        let mut arguments = vec![];
+        if self.frozen {
+            arguments.push("frozen=True");
+        }
+        if self.slots {
+            arguments.push("slots=True");
+        }
+
+        if arguments.is_empty() {
+            String::from("@dataclass")
+        } else {
+            format!("@dataclass({})", arguments.join(", "))
+        }
+    }
+}
+
+/// The number of properties listed by the generated `__repr__` method of a table without a primary key
+const REPR_FIELD_COUNT: usize = 3;
+
 /// Represents a full `TypedDict` definition in Python
 /// ```text
 /// class SomeDictionary(TypedDict):
@@ -138,26 +708,45 @@ impl From<bool> for ForcedBackwardCompat {
 ///     |
 ///     properties
 /// ```
-#[derive(Debug, PartialEq, PartialOrd)]
+///
+/// `raw_name` is the original database table name that `name` was derived from
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub(crate) struct PythonTypedDict {
     pub(crate) name: String,
+    pub(crate) raw_name: String,
     pub(crate) properties: Vec<PythonDictProperty>,
 }
 
 impl PythonTypedDict {
+    /// The name passed to `TypedDict(...)` in the functional syntax, which is `__name__` at runtime
+    fn functional_name(&self, name_source: TypedDictNameSource) -> &str {
+        match name_source {
+            TypedDictNameSource::Class => &self.name,
+            TypedDictNameSource::Table => &self.raw_name,
+        }
+    }
+
     /// Outputs a Python source string representation of this `TypedDict`
     pub(crate) fn as_typed_dict_class_str(
         &self,
         minimum_python_version: MinimumPythonVersion,
         forced_backward_compat: ForcedBackwardCompat,
+        totality: Totality,
+        name_source: TypedDictNameSource,
     ) -> String {
         let use_alternate_syntax = minimum_python_version == MinimumPythonVersion::Python3_6
             || forced_backward_compat == ForcedBackwardCompat::Enabled;
 
+        let total_argument = totality.as_argument_str();
+
         let mut result = if use_alternate_syntax {
-            format!("{} = TypedDict('{}', {{\n", self.name, self.name)
+            format!(
+                "{} = TypedDict('{}', {{\n",
+                self.name,
+                self.functional_name(name_source)
+            )
         } else {
-            format!("class {}(TypedDict):\n", self.name)
+            format!("class {}(TypedDict{}):\n", self.name, total_argument)
         };
 
         let middle_lines = self
@@ -169,17 +758,17 @@ impl PythonTypedDict {
                     (true, Position::Last) | (true, Position::Only) => format!(
                         "    '{}': {}", // final property doesn't need a trailing comma
                         property.name,
-                        property.as_property_type_str(minimum_python_version)
+                        totality.as_key_type_str(property, minimum_python_version)
                     ),
                     (true, _) => format!(
                         "    '{}': {},", // first/middle properties need a trailing comma with this syntax
                         property.name,
-                        property.as_property_type_str(minimum_python_version)
+                        totality.as_key_type_str(property, minimum_python_version)
                     ),
                     (false, _) => format!(
                         "    {}: {}",
                         property.name,
-                        property.as_property_type_str(minimum_python_version)
+                        totality.as_key_type_str(property, minimum_python_version)
                     ),
                 },
             )
@@ -190,9 +779,226 @@ impl PythonTypedDict {
         result.push('\n');
 
         if use_alternate_syntax {
-            result.push_str("})\n");
+            result.push_str(format!("}}{})\n", total_argument).as_str());
+        }
+
+        result
+    }
+
+    /// Outputs a single-line Python source string representation of this `TypedDict` with the
+    /// backward-compatible syntax, e.g. `Code = TypedDict('Code', {'id': int, 'label': str})`
+    pub(crate) fn as_compact_typed_dict_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+        totality: Totality,
+        name_source: TypedDictNameSource,
+    ) -> String {
+        let properties = self
+            .properties
+            .iter()
+            .map(|property| {
+                format!(
+                    "'{}': {}",
+                    property.name,
+                    totality.as_key_type_str(property, minimum_python_version)
+                )
+            })
+            .join(", ");
+
+        format!(
+            "{} = TypedDict('{}', {{{}}}{})\n",
+            self.name,
+            self.functional_name(name_source),
+            properties,
+            totality.as_argument_str()
+        )
+    }
+
+    /// The properties of this table in the field order of the class-based output styles, each with its default
+    ///
+    /// Nullable properties default to `None`, so they are placed after the non-nullable ones
+    /// to avoid the "non-default argument follows default argument" error
+    pub(crate) fn class_fields(
+        &self,
+    ) -> impl Iterator<Item = (&PythonDictProperty, Option<&'static str>)> {
+        self.properties
+            .iter()
+            .sorted_by_key(|property| property.nullable)
+            .map(|property| (property, property.nullable.then_some("None")))
+    }
+
+    /// Outputs a Python source string representation of this table as a `@dataclass`,
+    /// with the fields of `class_fields`
+    pub(crate) fn as_dataclass_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+        dataclass_options: DataclassOptions,
+    ) -> String {
+        let mut result = format!(
+            "{}\nclass {}:\n",
+            dataclass_options.as_decorator_str(),
+            self.name
+        );
+
+        let middle_lines = self
+            .class_fields()
+            .map(|(property, default)| match default {
+                Some(default) => format!(
+                    "    {}: {} = {}",
+                    property.name,
+                    property.as_property_type_str(minimum_python_version),
+                    default
+                ),
+                None => format!(
+                    "    {}: {}",
+                    property.name,
+                    property.as_property_type_str(minimum_python_version)
+                ),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        result.push_str(middle_lines.as_str());
+        result.push('\n');
+
+        result
+    }
+
+    /// Outputs a Python source string representation of this table as a `msgspec.Struct`,
+    /// with the fields of `class_fields`
+    pub(crate) fn as_msgspec_struct_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+    ) -> String {
+        let mut result = format!("class {}(msgspec.Struct):\n", self.name);
+
+        for (property, default) in self.class_fields() {
+            let type_str = property.as_property_type_str(minimum_python_version);
+            result.push_str(&match default {
+                Some(default) => format!("    {}: {} = {}\n", property.name, type_str, default),
+                None => format!("    {}: {}\n", property.name, type_str),
+            });
+        }
+
+        result
+    }
+
+    /// Outputs a Python source string representation of this table as a SQLAlchemy 2.0 declarative model
+    /// of the generated `Base`, mapped to the original table name, with a `Mapped[...]` column per property.
+    /// Columns whose annotation SQLAlchemy can't map on its own are given their column type explicitly
+    pub(crate) fn as_sqlalchemy_model_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+    ) -> String {
+        let mut result = format!(
+            "class {}(Base):\n    __tablename__ = {}\n\n",
+            self.name,
+            as_python_string_literal(&self.raw_name)
+        );
+
+        for property in &self.properties {
+            let column_type = (!property.data_type.is_sqlalchemy_annotation_mapped())
+                .then(|| property.data_type.as_sqlalchemy_type_str());
+            let primary_key = property
+                .primary_key
+                .then(|| String::from("primary_key=True"));
+            result.push_str(&format!(
+                "    {}: Mapped[{}] = mapped_column({})\n",
+                property.name,
+                property.as_property_type_str(minimum_python_version),
+                column_type.into_iter().chain(primary_key).join(", ")
+            ));
+        }
+
+        result
+    }
+
+    /// Outputs a concise `__repr__` method for the class-based output styles, listing the primary key properties,
+    /// or the first `REPR_FIELD_COUNT` properties without a primary key, so that rows are easy to tell apart
+    /// while debugging
+    pub(crate) fn as_repr_method_str(&self) -> String {
+        let primary_key = self
+            .properties
+            .iter()
+            .filter(|property| property.primary_key)
+            .collect::<Vec<&PythonDictProperty>>();
+        let properties = if primary_key.is_empty() {
+            self.properties.iter().take(REPR_FIELD_COUNT).collect()
+        } else {
+            primary_key
+        };
+
+        let fields = properties
+            .iter()
+            .map(|property| format!("{}={{self.{}!r}}", property.name, property.name))
+            .join(", ");
+
+        format!(
+            "\n    def __repr__(self) -> str:\n        return f\"{}({})\"\n",
+            self.name, fields
+        )
+    }
+
+    /// Outputs a Python source string representation of this table as a Polars schema dict,
+    /// which can be passed as e.g. the `schema_overrides` of `pl.read_database`
+    pub(crate) fn as_polars_schema_str(&self) -> String {
+        let mut result = format!("{} = {{\n", as_polars_schema_name(&self.name));
+
+        for property in &self.properties {
+            result.push_str(&format!(
+                "    {}: {},\n",
+                as_python_string_literal(&property.name),
+                property.data_type.as_polars_dtype_str()
+            ));
         }
 
+        result.push_str("}\n");
+
+        result
+    }
+
+    /// Outputs a Python source string representation of this table as a Pydantic `BaseModel`,
+    /// with the fields of `class_fields`, so nullable columns come after the non-nullable ones like in a dataclass
+    pub(crate) fn as_pydantic_model_str(
+        &self,
+        minimum_python_version: MinimumPythonVersion,
+        pydantic_json: Option<PydanticJson>,
+    ) -> String {
+        let mut result = format!("class {}(BaseModel):\n", self.name);
+
+        let middle_lines = self
+            .class_fields()
+            .map(|(property, default)| {
+                let type_str =
+                    property.as_pydantic_property_type_str(minimum_python_version, pydantic_json);
+
+                // pydantic requires a nullable field unless it has a default
+                match (&property.comment, default) {
+                    (Some(comment), Some(default)) => format!(
+                        "    {}: {} = Field(default={}, description={})",
+                        property.name,
+                        type_str,
+                        default,
+                        as_python_string_literal(comment)
+                    ),
+                    (Some(comment), None) => format!(
+                        "    {}: {} = Field(description={})",
+                        property.name,
+                        type_str,
+                        as_python_string_literal(comment)
+                    ),
+                    (None, Some(default)) => {
+                        format!("    {}: {} = {}", property.name, type_str, default)
+                    }
+                    (None, None) => format!("    {}: {}", property.name, type_str),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        result.push_str(middle_lines.as_str());
+        result.push('\n');
+
         result
     }
 }
@@ -207,6 +1013,10 @@ mod test {
             name: String::from("some_property"),
             nullable,
             data_type: PythonDataType::String,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
         }
     }
 
@@ -240,6 +1050,16 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_non_nullable_property_type_str_python_3_12() {
+        let pdp = get_str_some_property(false);
+
+        assert_eq!(
+            pdp.as_property_type_str(MinimumPythonVersion::Python3_12),
+            String::from("str")
+        );
+    }
+
     #[test]
     fn test_nullable_property_type_str_python_3_6() {
         let pdp = get_str_some_property(true);
@@ -270,17 +1090,30 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_nullable_property_type_str_python_3_12() {
+        let pdp = get_str_some_property(true);
+
+        assert_eq!(
+            pdp.as_property_type_str(MinimumPythonVersion::Python3_12),
+            String::from("str | None")
+        );
+    }
+
     #[test]
     fn test_typed_dict_class_str_python_3_6() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(false)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -292,7 +1125,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -306,13 +1141,16 @@ mod test {
     fn test_typed_dict_class_str_python_3_8() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(false)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -323,7 +1161,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -337,13 +1177,16 @@ mod test {
     fn test_typed_dict_class_str_python_3_10() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(false)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -354,7 +1197,45 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
+            ),
+            indoc! {"
+                TestTable = TypedDict('TestTable', {
+                    'some_property': str
+                })
+            "}
+        );
+    }
+
+    #[test]
+    fn test_typed_dict_class_str_python_3_12() {
+        let dict = PythonTypedDict {
+            name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
+            properties: vec![get_str_some_property(false)],
+        };
+
+        assert_eq!(
+            dict.as_typed_dict_class_str(
+                MinimumPythonVersion::Python3_12,
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
+            ),
+            indoc! {"
+                class TestTable(TypedDict):
+                    some_property: str
+            "}
+        );
+
+        assert_eq!(
+            dict.as_typed_dict_class_str(
+                MinimumPythonVersion::Python3_12,
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -368,16 +1249,25 @@ mod test {
     fn test_typed_dict_class_str_with_mult_properties_python_3_6() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::Boolean,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -385,7 +1275,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -398,7 +1290,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -413,16 +1307,25 @@ mod test {
     fn test_typed_dict_class_str_with_mult_properties_python_3_8() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::Boolean,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -430,7 +1333,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -442,7 +1347,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -457,16 +1364,25 @@ mod test {
     fn test_typed_dict_class_str_with_mult_properties_python_3_10() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::Boolean,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -474,7 +1390,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -486,7 +1404,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -501,13 +1421,16 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_property_python_3_6() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(true)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -519,7 +1442,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -533,13 +1458,16 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_property_python_3_8() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(true)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -550,7 +1478,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -564,13 +1494,16 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_property_python_3_10() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![get_str_some_property(true)],
         };
 
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -581,7 +1514,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -595,16 +1530,25 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_and_nonnull_property_python_3_6() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: true,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -612,7 +1556,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -625,7 +1571,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_6,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -640,16 +1588,25 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_and_nonnull_property_python_3_8() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: true,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -657,7 +1614,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -669,7 +1628,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_8,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -684,16 +1645,25 @@ mod test {
     fn test_typed_dict_class_str_with_nullable_and_nonnull_property_python_3_10() {
         let dict = PythonTypedDict {
             name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
             properties: vec![
                 PythonDictProperty {
                     name: String::from("some_property"),
                     nullable: true,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
                 PythonDictProperty {
                     name: String::from("some_other_property"),
                     nullable: false,
                     data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
                 },
             ],
         };
@@ -701,7 +1671,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Disabled
+                ForcedBackwardCompat::Disabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 class TestTable(TypedDict):
@@ -713,7 +1685,9 @@ mod test {
         assert_eq!(
             dict.as_typed_dict_class_str(
                 MinimumPythonVersion::Python3_10,
-                ForcedBackwardCompat::Enabled
+                ForcedBackwardCompat::Enabled,
+                Totality::Total,
+                TypedDictNameSource::Class
             ),
             indoc! {"
                 TestTable = TypedDict('TestTable', {
@@ -723,4 +1697,319 @@ mod test {
             "}
         );
     }
+
+    #[test]
+    fn test_dataclass_decorator_str() {
+        let decorator = |frozen, slots| DataclassOptions { frozen, slots }.as_decorator_str();
+
+        assert_eq!(decorator(false, false), "@dataclass");
+        assert_eq!(decorator(true, false), "@dataclass(frozen=True)");
+        assert_eq!(decorator(false, true), "@dataclass(slots=True)");
+        assert_eq!(decorator(true, true), "@dataclass(frozen=True, slots=True)");
+    }
+
+    #[test]
+    fn class_styles_share_field_order_and_defaults() {
+        let property = |name: &str, nullable, data_type| PythonDictProperty {
+            name: String::from(name),
+            nullable,
+            data_type,
+            comment: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+        };
+        let dict = PythonTypedDict {
+            name: String::from("Users"),
+            raw_name: String::from("users"),
+            properties: vec![
+                property("bio", true, PythonDataType::String),
+                property("id", false, PythonDataType::Integer),
+                property("age", true, PythonDataType::Integer),
+                property("email", false, PythonDataType::String),
+            ],
+        };
+
+        let fields = dict
+            .class_fields()
+            .map(|(property, default)| (property.name.as_str(), default))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("id", None),
+                ("email", None),
+                ("bio", Some("None")),
+                ("age", Some("None")),
+            ]
+        );
+
+        let field_lines = |class_str: String| {
+            class_str
+                .lines()
+                .skip_while(|line| !line.starts_with("class "))
+                .skip(1)
+                .map(String::from)
+                .collect::<Vec<String>>()
+        };
+        let expected = vec![
+            "    id: int",
+            "    email: str",
+            "    bio: str | None = None",
+            "    age: int | None = None",
+        ];
+        assert_eq!(
+            field_lines(dict.as_dataclass_str(
+                MinimumPythonVersion::Python3_10,
+                DataclassOptions::default()
+            )),
+            expected
+        );
+        assert_eq!(
+            field_lines(dict.as_pydantic_model_str(MinimumPythonVersion::Python3_10, None)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_dataclass_str_orders_nullable_properties_last() {
+        let dict = PythonTypedDict {
+            name: String::from("TestTable"),
+            raw_name: String::from("test_table"),
+            properties: vec![
+                get_str_some_property(true),
+                PythonDictProperty {
+                    name: String::from("some_other_property"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        };
+
+        assert_eq!(
+            dict.as_dataclass_str(
+                MinimumPythonVersion::Python3_10,
+                DataclassOptions {
+                    frozen: true,
+                    slots: true
+                }
+            ),
+            indoc! {"
+                @dataclass(frozen=True, slots=True)
+                class TestTable:
+                    some_other_property: int
+                    some_property: str | None = None
+            "}
+        );
+
+        assert_eq!(
+            dict.as_dataclass_str(MinimumPythonVersion::Python3_8, DataclassOptions::default()),
+            indoc! {"
+                @dataclass
+                class TestTable:
+                    some_other_property: int
+                    some_property: Optional[str] = None
+            "}
+        );
+    }
+
+    #[test]
+    fn test_python_data_type_from_str() {
+        assert_eq!(
+            PythonDataType::from_str("String"),
+            Ok(PythonDataType::String)
+        );
+        assert_eq!(
+            PythonDataType::from_str("datetime"),
+            Ok(PythonDataType::DateTime)
+        );
+        assert!(PythonDataType::from_str("varchar").is_err());
+    }
+
+    #[test]
+    fn maps_postgres_range_types_to_str() {
+        assert_eq!(
+            PythonDataType::from(String::from("int4range")),
+            PythonDataType::String
+        );
+        assert_eq!(
+            PythonDataType::from(String::from("tstzmultirange")),
+            PythonDataType::String
+        );
+    }
+
+    #[test]
+    fn maps_arrays_to_lists() {
+        assert_eq!(
+            PythonDataType::from(String::from("boolean[]")),
+            PythonDataType::List(Box::new(PythonDataType::Boolean))
+        );
+        let matrix = PythonDataType::from(String::from("integer[][]"));
+        assert_eq!(
+            matrix,
+            PythonDataType::List(Box::new(PythonDataType::List(Box::new(
+                PythonDataType::Integer
+            ))))
+        );
+        assert_eq!(matrix.as_primitive_type_str(), "list[list[int]]");
+        assert_eq!(
+            matrix.as_versioned_type_str(MinimumPythonVersion::Python3_8),
+            "List[List[int]]"
+        );
+    }
+
+    #[test]
+    fn maps_uuids() {
+        let uuid = PythonDataType::from(String::from("uuid"));
+        assert_eq!(uuid, PythonDataType::Uuid);
+        assert_eq!(uuid.as_primitive_type_str(), "uuid.UUID");
+        assert!(PythonDataType::List(Box::new(uuid)).uses(&PythonDataType::Uuid));
+        assert!(!PythonDataType::String.uses(&PythonDataType::Uuid));
+    }
+
+    #[test]
+    fn maps_times() {
+        // mysql, then postgres
+        for data_type in ["time", "time without time zone"] {
+            assert_eq!(
+                PythonDataType::from(String::from(data_type)),
+                PythonDataType::Time
+            );
+        }
+        assert_eq!(
+            PythonDataType::Time.as_primitive_type_str(),
+            "datetime.time"
+        );
+    }
+
+    #[test]
+    fn maps_postgres_intervals_to_timedeltas() {
+        let interval = PythonDataType::from(String::from("interval"));
+        assert_eq!(interval, PythonDataType::Interval);
+        assert_eq!(interval.as_primitive_type_str(), "datetime.timedelta");
+    }
+
+    #[test]
+    fn maps_exact_numerics_to_decimals() {
+        assert!(PythonDataType::is_exact_numeric("numeric"));
+        assert!(PythonDataType::is_exact_numeric("decimal[]"));
+        assert!(!PythonDataType::is_exact_numeric("double precision"));
+        assert_eq!(
+            PythonDataType::from(String::from("numeric[]")).with_decimals(),
+            PythonDataType::List(Box::new(PythonDataType::Decimal))
+        );
+        assert_eq!(
+            PythonDataType::Decimal.as_primitive_type_str(),
+            "decimal.Decimal"
+        );
+    }
+
+    #[test]
+    fn maps_mysql_enums_to_literals() {
+        assert_eq!(
+            PythonDataType::literal_from_mysql_enum("enum('pending','paid','it''s')"),
+            Some(PythonDataType::Literal(vec![
+                String::from("\"pending\""),
+                String::from("\"paid\""),
+                String::from("\"it's\""),
+            ]))
+        );
+        assert_eq!(
+            PythonDataType::literal_from_mysql_enum("varchar(255)"),
+            None
+        );
+        assert_eq!(
+            PythonDataType::literal_from_mysql_enum("enum('unterminated)"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_dialect_versions() {
+        assert_eq!("14".parse(), Ok(DialectVersion::new(14, 0)));
+        assert_eq!("9.4".parse(), Ok(DialectVersion::new(9, 4)));
+        assert_eq!("8.0.32".parse(), Ok(DialectVersion::new(8, 0)));
+        assert!("latest".parse::<DialectVersion>().is_err());
+    }
+
+    #[test]
+    fn gates_types_by_dialect_version() {
+        let multirange = || String::from("int4multirange");
+
+        assert_eq!(
+            PythonDataType::from_versioned(multirange(), Some(DialectVersion::new(13, 9))),
+            PythonDataType::Any
+        );
+        assert_eq!(
+            PythonDataType::from_versioned(multirange(), Some(DialectVersion::new(14, 0))),
+            PythonDataType::String
+        );
+        assert_eq!(
+            PythonDataType::from_versioned(multirange(), None),
+            PythonDataType::String
+        );
+        // types that aren't gated are mapped the same at any version
+        assert_eq!(
+            PythonDataType::from_versioned(String::from("text"), Some(DialectVersion::new(9, 0))),
+            PythonDataType::String
+        );
+    }
+
+    #[test]
+    fn maps_sql_server_types() {
+        for (data_type, expected) in [
+            ("nvarchar", PythonDataType::String),
+            ("uniqueidentifier", PythonDataType::Uuid),
+            ("bit", PythonDataType::Boolean),
+            ("datetime2", PythonDataType::DateTime),
+            ("money", PythonDataType::Float),
+            ("tinyint", PythonDataType::Integer),
+        ] {
+            assert_eq!(
+                PythonDataType::from_sql_server(String::from(data_type)),
+                expected
+            );
+        }
+        // mysql's `tinyint` is still a boolean
+        assert_eq!(
+            PythonDataType::from(String::from("tinyint")),
+            PythonDataType::Boolean
+        );
+    }
+
+    #[test]
+    fn builds_literal_from_distinct_values() {
+        let values = vec![
+            String::from("pending"),
+            String::from("paid"),
+            String::from("it's \"refunded\""),
+        ];
+
+        assert_eq!(
+            PythonDataType::literal_from_distinct_values(&values, 3)
+                .unwrap()
+                .as_primitive_type_str(),
+            r#"Literal["it's \"refunded\"", "paid", "pending"]"#
+        );
+
+        // too many distinct values to plausibly be an enum
+        assert_eq!(
+            PythonDataType::literal_from_distinct_values(&values, 2),
+            None
+        );
+        assert_eq!(PythonDataType::literal_from_distinct_values(&[], 3), None);
+    }
+
+    #[test]
+    fn escapes_python_string_literals() {
+        assert_eq!(as_python_string_literal("plain"), r#""plain""#);
+        assert_eq!(
+            as_python_string_literal("say \"hi\"\\now\nthen\u{7}"),
+            r#""say \"hi\"\\now\nthen\x07""#
+        );
+    }
 }
@@ -0,0 +1,101 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::Context;
+
+/// The Python interpreter used to check the generated source
+const PYTHON_INTERPRETER: &str = "python3";
+
+/// Compiles the source read from stdin without running it, and reports the first syntax error
+/// with its line number and the offending line
+const VALIDATION_SCRIPT: &str = r#"
+import sys
+
+source = sys.stdin.read()
+try:
+    compile(source, "<generated>", "exec")
+except SyntaxError as error:
+    print(f"line {error.lineno}: {error.msg}: {(error.text or '').strip()}", file=sys.stderr)
+    sys.exit(1)
+"#;
+
+/// Checks that the generated Python source is syntactically valid by compiling it with `python3`,
+/// which catches renderer edge cases (e.g. an unhandled identifier) before anything is written.
+pub(crate) fn validate_python_source(source: &str) -> Result<(), anyhow::Error> {
+    let mut child = Command::new(PYTHON_INTERPRETER)
+        .args(["-c", VALIDATION_SCRIPT])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!(
+            "Unable to run `{PYTHON_INTERPRETER}` to validate the generated source"
+        ))?;
+
+    // writes stdin on its own thread, like `post_process`, so a large file can't deadlock us
+    let mut stdin = child.stdin.take().context("Unable to open stdin")?;
+    let input = source.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("Unable to write to `{PYTHON_INTERPRETER}`"))??;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "The generated Python source is invalid, {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::{
+        python_type_file_writer::{write_python_dicts_to_str, WriterOptions},
+        python_types::{PythonDataType, PythonDictProperty, PythonTypedDict},
+    };
+
+    fn dict_named(name: &str) -> PythonTypedDict {
+        PythonTypedDict {
+            name: String::from(name),
+            raw_name: String::from(name),
+            properties: vec![PythonDictProperty {
+                name: String::from("id"),
+                nullable: false,
+                data_type: PythonDataType::Integer,
+                comment: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn accepts_valid_output() {
+        let source =
+            write_python_dicts_to_str(vec![dict_named("Users")], &WriterOptions::default());
+
+        assert!(validate_python_source(&source).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_output_with_the_offending_line() {
+        // a class name with a space isn't a valid identifier
+        let source =
+            write_python_dicts_to_str(vec![dict_named("User Accounts")], &WriterOptions::default());
+
+        let error = validate_python_source(&source).unwrap_err().to_string();
+
+        assert!(error.contains("line 10"), "{error}");
+        assert!(error.contains("class User Accounts(TypedDict):"), "{error}");
+    }
+}
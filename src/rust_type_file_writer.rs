@@ -0,0 +1,294 @@
+use convert_case::{Case, Casing};
+use itertools::Itertools;
+
+use crate::python_types::{PythonDataType, PythonDictProperty, PythonTypedDict};
+
+/// Rust keywords that can't be used as field names without the raw identifier (`r#`) prefix
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Rust keywords that are not allowed as raw identifiers either, so they get a trailing `_` instead
+const RUST_NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+impl PythonDataType {
+    /// Convert a `PythonDataType` into its Rust source code type representation
+    pub(crate) fn as_rust_type_str(&self) -> String {
+        match self {
+            PythonDataType::String => "String",
+            PythonDataType::Integer => "i64",
+            PythonDataType::Float => "f64",
+            PythonDataType::Decimal => "String", // avoids requiring a decimal crate, without losing precision
+            PythonDataType::Boolean => "bool",
+            PythonDataType::DateTime => "chrono::NaiveDateTime",
+            PythonDataType::AwareDateTime => "chrono::DateTime<chrono::Utc>",
+            PythonDataType::Date => "chrono::NaiveDate",
+            PythonDataType::Time => "chrono::NaiveTime",
+            PythonDataType::Interval => "chrono::Duration",
+            PythonDataType::Binary => "Vec<u8>",
+            PythonDataType::Json => "String",
+            PythonDataType::Uuid => "String", // avoids requiring the `uuid` crate
+            PythonDataType::List(element) => return format!("Vec<{}>", element.as_rust_type_str()),
+            PythonDataType::Literal(_) => "String",
+            PythonDataType::NewType { base, .. } => return base.as_rust_type_str(),
+            PythonDataType::Nested(name) => return name.clone(),
+            PythonDataType::Any => "String", // unknown database types are read as their text representation
+        }
+        .to_string()
+    }
+}
+
+impl PythonDictProperty {
+    /// Builds the snake_case Rust field name for this property, replacing the characters identifiers can't hold
+    /// and escaping Rust keywords, e.g. `field_2_fa_enabled` for `2fa enabled` or `r#type` for `type`
+    pub(crate) fn as_rust_field_name(&self) -> String {
+        let name = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+            .to_case(Case::Snake);
+
+        if name.is_empty() {
+            String::from("field")
+        } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("field_{name}")
+        } else if RUST_NON_RAW_KEYWORDS.contains(&name.as_str()) {
+            format!("{name}_")
+        } else if RUST_KEYWORDS.contains(&name.as_str()) {
+            format!("r#{name}")
+        } else {
+            name
+        }
+    }
+
+    /// Builds a string representing the Rust type of the given `PythonDictProperty`
+    pub(crate) fn as_rust_field_type_str(&self) -> String {
+        if self.nullable {
+            format!("Option<{}>", self.data_type.as_rust_type_str())
+        } else {
+            self.data_type.as_rust_type_str()
+        }
+    }
+}
+
+impl PythonTypedDict {
+    /// Outputs a Rust source string representation of this table as a `struct`, numbering the field names
+    /// of columns that end up the same, e.g. `e_mail` and `e_mail_2` for `e-mail` and `E Mail`
+    pub(crate) fn as_rust_struct_str(&self) -> String {
+        let mut result = format!("#[derive(Debug)]\npub struct {} {{\n", self.name);
+
+        let mut field_names = Vec::<String>::new();
+        for property in &self.properties {
+            let field_name = property.as_rust_field_name();
+            let field_name = (1..)
+                .map(|n| match n {
+                    1 => field_name.clone(),
+                    n => format!("{field_name}_{n}"),
+                })
+                .find(|candidate| !field_names.contains(candidate))
+                .unwrap();
+            result.push_str(
+                format!(
+                    "    pub {}: {},\n",
+                    field_name,
+                    property.as_rust_field_type_str()
+                )
+                .as_str(),
+            );
+            field_names.push(field_name);
+        }
+
+        result.push_str("}\n");
+
+        result
+    }
+}
+
+/// Writes the `Vec<PythonTypedDict>` into a Rust source string of `struct` definitions
+/// that can then later be written to a file inside `main()`
+pub(crate) fn write_rust_structs_to_str(dicts: Vec<PythonTypedDict>) -> String {
+    let mut result = String::from(
+        "// This file was generated by db-introspector-gadget\n// https://github.com/sesgoe/db-introspector-gadget\n\n",
+    );
+
+    let uses_chrono = dicts.iter().flat_map(|dict| &dict.properties).any(|p| {
+        matches!(
+            p.data_type,
+            PythonDataType::DateTime
+                | PythonDataType::AwareDateTime
+                | PythonDataType::Date
+                | PythonDataType::Time
+                | PythonDataType::Interval
+        )
+    });
+
+    if uses_chrono {
+        result.push_str("// This file requires the `chrono` crate\n\n");
+    }
+
+    let rust_structs_str = dicts
+        .iter()
+        .filter(|dict| !dict.name.contains('$')) // prevents weirdness with some system tables
+        .filter(|dict| !dict.name.chars().next().unwrap().is_numeric())
+        .sorted_by_key(|f| f.name.clone())
+        .map(|dict| dict.as_rust_struct_str())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    result.push_str(rust_structs_str.as_str());
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_rust_struct_str() {
+        let dict = PythonTypedDict {
+            name: String::from("User"),
+            raw_name: String::from("user"),
+            properties: vec![
+                PythonDictProperty {
+                    name: String::from("id"),
+                    nullable: false,
+                    data_type: PythonDataType::Integer,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("name"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("type"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("self"),
+                    nullable: true,
+                    data_type: PythonDataType::Binary,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("2fa enabled"),
+                    nullable: false,
+                    data_type: PythonDataType::Boolean,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("e-mail"),
+                    nullable: false,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+                PythonDictProperty {
+                    name: String::from("E Mail"),
+                    nullable: true,
+                    data_type: PythonDataType::String,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                },
+            ],
+        };
+
+        assert_eq!(
+            dict.as_rust_struct_str(),
+            indoc! {"
+                #[derive(Debug)]
+                pub struct User {
+                    pub id: i64,
+                    pub name: Option<String>,
+                    pub r#type: String,
+                    pub self_: Option<Vec<u8>>,
+                    pub field_2_fa_enabled: bool,
+                    pub e_mail: String,
+                    pub e_mail_2: Option<String>,
+                }
+            "}
+        );
+    }
+
+    #[test]
+    fn writes_rust_structs_to_string() {
+        let dicts = vec![
+            PythonTypedDict {
+                name: String::from("BTable"),
+                raw_name: String::from("b_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("created_at"),
+                    nullable: false,
+                    data_type: PythonDataType::DateTime,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+            PythonTypedDict {
+                name: String::from("ATable"),
+                raw_name: String::from("a_table"),
+                properties: vec![PythonDictProperty {
+                    name: String::from("price"),
+                    nullable: true,
+                    data_type: PythonDataType::Float,
+                    comment: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                }],
+            },
+        ];
+
+        assert_eq!(
+            write_rust_structs_to_str(dicts),
+            indoc! {"
+                // This file was generated by db-introspector-gadget
+                // https://github.com/sesgoe/db-introspector-gadget
+
+                // This file requires the `chrono` crate
+
+                #[derive(Debug)]
+                pub struct ATable {
+                    pub price: Option<f64>,
+                }
+
+                #[derive(Debug)]
+                pub struct BTable {
+                    pub created_at: chrono::NaiveDateTime,
+                }
+            "}
+        );
+    }
+}
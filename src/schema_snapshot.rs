@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::db_introspector::TableColumnDefinition;
+
+/// The version of the snapshot format, bumped whenever a change would break reading older snapshots
+const SCHEMA_SNAPSHOT_VERSION: u32 = 1;
+
+/// A JSON snapshot of the full introspected schema, written to a sidecar file so that a future run can diff against it.
+///
+/// It records the introspection data itself rather than any generated output, so it is the same for every output format.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SchemaSnapshot {
+    pub(crate) version: u32,
+    pub(crate) schemas: Vec<String>,
+    pub(crate) columns: Vec<TableColumnDefinition>,
+}
+
+impl SchemaSnapshot {
+    pub(crate) fn new(schemas: &[String], columns: Vec<TableColumnDefinition>) -> Self {
+        SchemaSnapshot {
+            version: SCHEMA_SNAPSHOT_VERSION,
+            schemas: schemas.to_vec(),
+            columns,
+        }
+    }
+
+    /// Serializes the snapshot as pretty-printed JSON, so that it diffs well under version control
+    pub(crate) fn to_json(&self) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Reads a snapshot written by `to_json`, rejecting snapshots of other format versions
+    pub(crate) fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        let snapshot: SchemaSnapshot = serde_json::from_str(json)?;
+        if snapshot.version != SCHEMA_SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "Unsupported schema snapshot version {}, expected {}",
+                snapshot.version,
+                SCHEMA_SNAPSHOT_VERSION
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Lists the columns of this snapshot whose type or nullability is different in `current`, one change per line,
+    /// e.g. `users.email: nullable -> not null`. Added and removed tables and columns are left out, since they're
+    /// not what breaks code that reads existing columns
+    pub(crate) fn changed_columns_report(&self, current: &[TableColumnDefinition]) -> String {
+        let current_columns = current
+            .iter()
+            .map(|column| ((&column.table_name, &column.column_name), column))
+            .collect::<HashMap<_, _>>();
+        let nullability = |nullable: bool| if nullable { "nullable" } else { "not null" };
+
+        let changes = self
+            .columns
+            .iter()
+            .sorted_by(|a, b| (&a.table_name, &a.column_name).cmp(&(&b.table_name, &b.column_name)))
+            .filter_map(|previous| {
+                let current =
+                    current_columns.get(&(&previous.table_name, &previous.column_name))?;
+                let mut changes = vec![];
+                if previous.data_type != current.data_type {
+                    changes.push(format!("{} -> {}", previous.data_type, current.data_type));
+                }
+                if previous.nullable != current.nullable {
+                    changes.push(format!(
+                        "{} -> {}",
+                        nullability(previous.nullable),
+                        nullability(current.nullable)
+                    ));
+                }
+                (!changes.is_empty()).then(|| {
+                    format!(
+                        "{}.{}: {}\n",
+                        previous.table_name,
+                        previous.column_name,
+                        changes.join(", ")
+                    )
+                })
+            })
+            .collect::<String>();
+
+        if changes.is_empty() {
+            String::from("No existing columns changed type or nullability\n")
+        } else {
+            changes
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_schema_through_json() {
+        let snapshot = SchemaSnapshot::new(
+            &[String::from("public")],
+            vec![
+                TableColumnDefinition {
+                    table_name: String::from("users"),
+                    column_name: String::from("email"),
+                    nullable: true,
+                    data_type: String::from("character varying"),
+                    comment: Some(String::from("Where receipts are sent")),
+                    distinct_values: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                    column_type: None,
+                    schema: None,
+                },
+                TableColumnDefinition {
+                    table_name: String::from("users"),
+                    column_name: String::from("id"),
+                    nullable: false,
+                    data_type: String::from("integer"),
+                    comment: None,
+                    distinct_values: None,
+                    max_length: None,
+                    check_bounds: vec![],
+                    primary_key: false,
+                    column_type: None,
+                    schema: None,
+                },
+            ],
+        );
+
+        let json = snapshot.to_json().unwrap();
+
+        assert_eq!(SchemaSnapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn reports_only_changed_columns() {
+        let column = |table_name: &str, column_name: &str, nullable: bool, data_type: &str| {
+            TableColumnDefinition {
+                table_name: String::from(table_name),
+                column_name: String::from(column_name),
+                nullable,
+                data_type: String::from(data_type),
+                comment: None,
+                distinct_values: None,
+                max_length: None,
+                check_bounds: vec![],
+                primary_key: false,
+                column_type: None,
+                schema: None,
+            }
+        };
+        let previous = SchemaSnapshot::new(
+            &[String::from("public")],
+            vec![
+                column("users", "id", false, "integer"),
+                column("users", "email", true, "character varying"),
+                column("users", "score", true, "integer"),
+                column("orders", "total", false, "numeric"),
+                column("legacy", "id", false, "integer"),
+            ],
+        );
+        let current = vec![
+            column("users", "id", false, "bigint"),
+            column("users", "email", false, "text"),
+            column("users", "score", false, "integer"),
+            column("users", "nickname", true, "text"),
+            column("orders", "total", false, "numeric"),
+        ];
+
+        assert_eq!(
+            previous.changed_columns_report(&current),
+            "users.email: character varying -> text, nullable -> not null\n\
+             users.id: integer -> bigint\n\
+             users.score: nullable -> not null\n"
+        );
+        assert_eq!(
+            previous.changed_columns_report(&previous.columns),
+            "No existing columns changed type or nullability\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let json = r#"{"version": 999, "schemas": [], "columns": []}"#;
+
+        assert!(SchemaSnapshot::from_json(json).is_err());
+    }
+}
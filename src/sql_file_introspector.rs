@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use sqlparser::{
+    ast::{
+        BinaryOperator, CharacterLength, ColumnOption, CommentObject, CreateTable, DataType, Expr,
+        Statement, TableConstraint, UnaryOperator, Value,
+    },
+    dialect::GenericDialect,
+    parser::Parser,
+};
+
+use crate::db_introspector::{CheckBound, TableColumnDefinition};
+
+/// Parses a SQL schema dump and converts its `CREATE TABLE` statements into a `Vec<TableColumnDefinition>`,
+/// the same shape that `get_table_definitions` returns from a live database.
+///
+/// Column comments are read from both inline `COMMENT '...'` column options (MySQL) and
+/// `COMMENT ON COLUMN` statements (Postgres), and the bounds of numeric columns from `CHECK` constraints.
+/// All other statements in the dump are ignored. Tables qualified with a schema (`public.users`) are only
+/// included if that schema is one of the provided `schemas`, while unqualified tables are always included.
+pub(crate) fn get_table_definitions_from_sql(
+    sql: &str,
+    schemas: &[String],
+) -> Result<Vec<TableColumnDefinition>, anyhow::Error> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql)?;
+
+    let in_schemas = |object_name: &[sqlparser::ast::Ident]| match object_name {
+        [.., table_schema, _] => schemas.contains(&table_schema.value),
+        _ => true,
+    };
+
+    // keyed by (table name, column name)
+    let column_comments = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::Comment {
+                object_type: CommentObject::Column,
+                object_name,
+                comment: Some(comment),
+                ..
+            } => match object_name.0.as_slice() {
+                [table_path @ .., column] if in_schemas(table_path) => table_path
+                    .last()
+                    .map(|table| ((table.value.clone(), column.value.clone()), comment.clone())),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<HashMap<(String, String), String>>();
+
+    let result = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::CreateTable(create_table) => Some(create_table),
+            _ => None,
+        })
+        .filter(|create_table| in_schemas(&create_table.name.0))
+        .flat_map(|create_table| {
+            convert_create_table_to_table_column_definitions(create_table, &column_comments)
+        })
+        .sorted_by(|a, b| (&a.table_name, &a.column_name).cmp(&(&b.table_name, &b.column_name)))
+        .collect::<Vec<TableColumnDefinition>>();
+
+    Ok(result)
+}
+
+/// Converts a single parsed `CREATE TABLE` statement into one `TableColumnDefinition` per column
+fn convert_create_table_to_table_column_definitions(
+    create_table: &CreateTable,
+    column_comments: &HashMap<(String, String), String>,
+) -> Vec<TableColumnDefinition> {
+    let table_name = create_table
+        .name
+        .0
+        .last()
+        .map(|ident| ident.value.clone())
+        .unwrap_or_default();
+
+    // columns named in a table-level `PRIMARY KEY (...)` constraint are implicitly `NOT NULL`
+    let primary_key_columns = create_table
+        .constraints
+        .iter()
+        .flat_map(|constraint| match constraint {
+            TableConstraint::PrimaryKey { columns, .. } => columns.clone(),
+            _ => vec![],
+        })
+        .map(|ident| ident.value)
+        .collect::<Vec<String>>();
+
+    let table_checks = create_table
+        .constraints
+        .iter()
+        .filter_map(|constraint| match constraint {
+            TableConstraint::Check { expr, .. } => Some(expr.as_ref()),
+            _ => None,
+        })
+        .collect::<Vec<&Expr>>();
+
+    create_table
+        .columns
+        .iter()
+        .map(|column| {
+            let primary_key = column.options.iter().any(|option_def| {
+                matches!(
+                    option_def.option,
+                    ColumnOption::Unique {
+                        is_primary: true,
+                        ..
+                    }
+                )
+            }) || primary_key_columns.contains(&column.name.value);
+            let not_null = primary_key
+                || column
+                    .options
+                    .iter()
+                    .any(|option_def| matches!(option_def.option, ColumnOption::NotNull));
+
+            let comment = column
+                .options
+                .iter()
+                .find_map(|option_def| match &option_def.option {
+                    ColumnOption::Comment(comment) => Some(comment.clone()),
+                    _ => None,
+                })
+                .or_else(|| {
+                    column_comments
+                        .get(&(table_name.clone(), column.name.value.clone()))
+                        .cloned()
+                });
+
+            let checks = column
+                .options
+                .iter()
+                .filter_map(|option_def| match &option_def.option {
+                    ColumnOption::Check(expr) => Some(expr),
+                    _ => None,
+                })
+                .chain(table_checks.iter().copied());
+
+            TableColumnDefinition {
+                table_name: table_name.clone(),
+                column_name: column.name.value.clone(),
+                nullable: !not_null,
+                data_type: normalize_data_type(&column.data_type),
+                comment,
+                distinct_values: None,
+                max_length: max_length(&column.data_type),
+                check_bounds: check_bounds(&column.name.value, checks),
+                primary_key,
+                column_type: column_type(&column.data_type),
+                schema: None,
+            }
+        })
+        .collect()
+}
+
+/// Reads the length argument of a character type like `varchar(50)`, which INFORMATION_SCHEMA.COLUMNS
+/// reports as `character_maximum_length`
+fn max_length(data_type: &DataType) -> Option<u64> {
+    match data_type {
+        DataType::Character(length)
+        | DataType::Char(length)
+        | DataType::CharacterVarying(length)
+        | DataType::CharVarying(length)
+        | DataType::Varchar(length)
+        | DataType::Nvarchar(length) => match length {
+            Some(CharacterLength::IntegerLength { length, .. }) => Some(*length),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Spells a `tinyint` with a display width the way MySQL's INFORMATION_SCHEMA.COLUMNS reports its
+/// `column_type`, since the width is what tells a `tinyint(1)` boolean apart from a small integer
+fn column_type(data_type: &DataType) -> Option<String> {
+    match data_type {
+        DataType::TinyInt(Some(width)) => Some(format!("tinyint({width})")),
+        _ => None,
+    }
+}
+
+/// Collects the bounds that `CHECK` constraints put on a column, e.g. `ge` and `0` for `CHECK (age >= 0)`.
+///
+/// Only comparisons between the column and a number are understood, on their own, joined with `AND`,
+/// or as a `BETWEEN`, so constraints like `CHECK (start_at < end_at)` are left out
+fn check_bounds<'a>(column_name: &str, checks: impl Iterator<Item = &'a Expr>) -> Vec<CheckBound> {
+    let mut bounds = vec![];
+    for check in checks {
+        collect_check_bounds(check, column_name, &mut bounds);
+    }
+    bounds
+}
+
+fn collect_check_bounds(expr: &Expr, column_name: &str, bounds: &mut Vec<CheckBound>) {
+    let mut push = |keyword: &str, value: String| {
+        bounds.push(CheckBound {
+            keyword: keyword.to_string(),
+            value,
+        })
+    };
+
+    match expr {
+        Expr::Nested(expr) => collect_check_bounds(expr, column_name, bounds),
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            collect_check_bounds(left, column_name, bounds);
+            collect_check_bounds(right, column_name, bounds);
+        }
+        Expr::BinaryOp { left, op, right } => {
+            // `0 <= age` bounds the column the same way as `age >= 0`
+            let (keyword, value) = if names_column(left, column_name) {
+                match op {
+                    BinaryOperator::GtEq => ("ge", number_literal(right)),
+                    BinaryOperator::Gt => ("gt", number_literal(right)),
+                    BinaryOperator::LtEq => ("le", number_literal(right)),
+                    BinaryOperator::Lt => ("lt", number_literal(right)),
+                    _ => return,
+                }
+            } else if names_column(right, column_name) {
+                match op {
+                    BinaryOperator::GtEq => ("le", number_literal(left)),
+                    BinaryOperator::Gt => ("lt", number_literal(left)),
+                    BinaryOperator::LtEq => ("ge", number_literal(left)),
+                    BinaryOperator::Lt => ("gt", number_literal(left)),
+                    _ => return,
+                }
+            } else {
+                return;
+            };
+
+            if let Some(value) = value {
+                push(keyword, value);
+            }
+        }
+        Expr::Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        } if names_column(expr, column_name) => {
+            if let (Some(low), Some(high)) = (number_literal(low), number_literal(high)) {
+                push("ge", low);
+                push("le", high);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn names_column(expr: &Expr, column_name: &str) -> bool {
+    match expr {
+        Expr::Identifier(ident) => ident.value.eq_ignore_ascii_case(column_name),
+        Expr::CompoundIdentifier(idents) => idents
+            .last()
+            .is_some_and(|ident| ident.value.eq_ignore_ascii_case(column_name)),
+        Expr::Nested(expr) => names_column(expr, column_name),
+        _ => false,
+    }
+}
+
+/// Reads a number literal, like `0`, `-10`, or `(1.5)`, as it's written
+fn number_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Value(Value::Number(number, _)) => Some(number.to_string()),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => number_literal(expr).map(|number| format!("-{number}")),
+        Expr::Nested(expr) => number_literal(expr),
+        _ => None,
+    }
+}
+
+/// Normalizes a DDL column type into the spelling INFORMATION_SCHEMA.COLUMNS reports for `data_type`,
+/// so that it can be mapped through the same `From<String>` logic as a live database.
+///
+/// e.g. `VARCHAR(255)` becomes `varchar`, `SERIAL` becomes `integer`, and `BOOL[][]` becomes `boolean[][]`,
+/// which is how the Postgres introspection query spells arrays
+fn normalize_data_type(data_type: &DataType) -> String {
+    let data_type = data_type.to_string().to_lowercase();
+
+    // array dimensions can have a size, like `int[3]`, which postgres ignores
+    let (data_type, dimensions) = match data_type.find('[') {
+        Some(open) => (
+            data_type[..open].to_string(),
+            data_type[open..].matches('[').count(),
+        ),
+        None => (data_type, 0),
+    };
+
+    // strips type arguments like `varchar(255)` or `decimal(10, 2)`
+    let data_type = match (data_type.find('('), data_type.rfind(')')) {
+        (Some(open), Some(close)) if open < close => {
+            format!("{}{}", &data_type[..open], &data_type[close + 1..])
+        }
+        _ => data_type,
+    };
+
+    let data_type = data_type
+        .replace(" unsigned", "")
+        .split_whitespace()
+        .join(" ");
+
+    let element_type = match data_type.as_str() {
+        "int4" | "serial" | "serial4" => "integer",
+        "int8" | "bigserial" | "serial8" => "bigint",
+        "int2" | "smallserial" | "serial2" => "smallint",
+        "bool" => "boolean",
+        "float8" => "double precision",
+        "timestamptz" => "timestamp with time zone",
+        other => other,
+    };
+
+    format!("{}{}", element_type, "[]".repeat(dimensions))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn column(
+        table_name: &str,
+        column_name: &str,
+        nullable: bool,
+        data_type: &str,
+    ) -> TableColumnDefinition {
+        TableColumnDefinition {
+            table_name: String::from(table_name),
+            column_name: String::from(column_name),
+            nullable,
+            data_type: String::from(data_type),
+            comment: None,
+            distinct_values: None,
+            max_length: None,
+            check_bounds: vec![],
+            primary_key: false,
+            column_type: None,
+            schema: None,
+        }
+    }
+
+    #[test]
+    fn parses_postgres_create_table_statements() {
+        let sql = r#"
+            CREATE TABLE public.users (
+                id SERIAL PRIMARY KEY,
+                "name" VARCHAR(255) NOT NULL,
+                email CHARACTER VARYING(255),
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+            );
+
+            CREATE INDEX users_email_idx ON public.users (email);
+
+            CREATE TABLE other_schema.ignored (
+                id INTEGER NOT NULL
+            );
+        "#;
+
+        let result = get_table_definitions_from_sql(sql, &[String::from("public")]).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                column("users", "created_at", false, "timestamp with time zone"),
+                TableColumnDefinition {
+                    max_length: Some(255),
+                    check_bounds: vec![],
+                    ..column("users", "email", true, "character varying")
+                },
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("users", "id", false, "integer")
+                },
+                TableColumnDefinition {
+                    max_length: Some(255),
+                    check_bounds: vec![],
+                    ..column("users", "name", false, "varchar")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_mysql_create_table_statements() {
+        let sql = "
+            CREATE TABLE `orders` (
+                `order_id` INT(11) UNSIGNED NOT NULL,
+                `total` DECIMAL(10, 2) NULL,
+                `is_paid` TINYINT(1) NOT NULL,
+                PRIMARY KEY (`order_id`)
+            );
+        ";
+
+        let result = get_table_definitions_from_sql(sql, &[String::from("testing")]).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                TableColumnDefinition {
+                    column_type: Some(String::from("tinyint(1)")),
+                    ..column("orders", "is_paid", false, "tinyint")
+                },
+                TableColumnDefinition {
+                    primary_key: true,
+                    ..column("orders", "order_id", false, "int")
+                },
+                column("orders", "total", true, "decimal"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_column_comments() {
+        let sql = "
+            CREATE TABLE public.users (
+                id INTEGER NOT NULL,
+                email TEXT
+            );
+            COMMENT ON COLUMN public.users.email IS 'Where receipts are sent';
+
+            CREATE TABLE orders (
+                status VARCHAR(16) NOT NULL COMMENT 'pending, paid, or refunded'
+            );
+        ";
+
+        let result = get_table_definitions_from_sql(sql, &[String::from("public")]).unwrap();
+
+        let comments = result
+            .iter()
+            .map(|column| (column.column_name.as_str(), column.comment.as_deref()))
+            .collect::<Vec<(&str, Option<&str>)>>();
+
+        assert_eq!(
+            comments,
+            vec![
+                ("status", Some("pending, paid, or refunded")),
+                ("email", Some("Where receipts are sent")),
+                ("id", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_check_constraint_bounds() {
+        let sql = "
+            CREATE TABLE items (
+                quantity INT CHECK (quantity > 0 AND quantity <= 1000),
+                discount NUMERIC CHECK (-1.5 < discount),
+                starts_at INT,
+                ends_at INT,
+                CHECK (starts_at < ends_at),
+                CHECK (ends_at BETWEEN 1 AND 9)
+            );
+        ";
+
+        let bound = |keyword: &str, value: &str| CheckBound {
+            keyword: String::from(keyword),
+            value: String::from(value),
+        };
+
+        let bounds = get_table_definitions_from_sql(sql, &[String::from("public")])
+            .unwrap()
+            .into_iter()
+            .map(|column| (column.column_name, column.check_bounds))
+            .collect::<Vec<(String, Vec<CheckBound>)>>();
+
+        assert_eq!(
+            bounds,
+            vec![
+                (String::from("discount"), vec![bound("gt", "-1.5")]),
+                (
+                    String::from("ends_at"),
+                    vec![bound("ge", "1"), bound("le", "9")]
+                ),
+                (
+                    String::from("quantity"),
+                    vec![bound("gt", "0"), bound("le", "1000")]
+                ),
+                (String::from("starts_at"), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_array_columns() {
+        let sql = "CREATE TABLE grids (flags BOOL[] NOT NULL, cells INT4[3][3]);";
+
+        assert_eq!(
+            get_table_definitions_from_sql(sql, &[String::from("public")]).unwrap(),
+            vec![
+                column("grids", "cells", true, "integer[][]"),
+                column("grids", "flags", false, "boolean[]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_sql() {
+        assert!(
+            get_table_definitions_from_sql("CREATE TABLE (", &[String::from("public")]).is_err()
+        );
+    }
+}